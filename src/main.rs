@@ -8,6 +8,7 @@ use crate::snn::resilience::Resilience;
 use snn::console_input_nn_creator::create_neural_network_from_user_input;
 use snn::json_adapter::{InputJson, NeuronJson};
 use crate::snn::json_adapter::{PathsJson, ResilienceJson};
+use crate::snn::dataset::Dataset;
 
 fn main() {
     let creation_mode = read_snn_creation_mode();
@@ -32,6 +33,29 @@ fn main() {
 
         // let configuration: Resilience = Resilience::new(vec!["Neurons".to_string()], Stuck::One, 1000);
         let configuration: Result<Resilience,String> = ResilienceJson::read_from_file(&paths.resilience).expect("Errore lettura file").to_resilience();
+        if let Some(compare) = &paths.compare {
+            let other = NeuronJson::read_from_file(&compare.layers, &compare.weights, &compare.configurations);
+            match (configuration, nn, other) {
+                (Ok(configuration), Ok(nn), Ok(other)) => match configuration.compare(nn, other, input, compare.seed) {
+                    Ok(report) => println!("{}", report),
+                    Err(err) => eprintln!("Error comparing the neural networks: {}", err),
+                },
+                (Err(err), _, _) => eprintln!("Error reading the resilience configuration: {}", err),
+                (_, Err(err), _) | (_, _, Err(err)) => eprintln!("Error reading the neural networks: {}", err),
+            }
+            return;
+        }
+        if let Some(dataset) = &paths.dataset {
+            match (configuration, nn, Dataset::read_from_dir(dataset)) {
+                (Ok(configuration), Ok(nn), Ok(dataset)) => {
+                    configuration.execute_dataset_test(nn, &dataset);
+                }
+                (Err(err), _, _) => eprintln!("Error reading the resilience configuration: {}", err),
+                (_, Err(err), _) => eprintln!("Error reading the neural network: {}", err),
+                (_, _, Err(err)) => eprintln!("Error reading the dataset: {}", err),
+            }
+            return;
+        }
         configuration.ok().unwrap().execute_resilience_test(nn.clone().unwrap(),input);
         return;
     } else {
@@ -0,0 +1,167 @@
+//! # Comparison of Network Variants
+//!
+//! Is a retrained or re-quantized network more robust than the previous one? A comparison runs the same
+//! fault campaign on two networks with the same topology: the faults are drawn once, from a seeded random
+//! number generator, and every fault is injected in both networks. Every faulty run is either masked (the
+//! output is the one of the same network without faults) or a silent data corruption (SDC).
+//!
+//! Since the two networks see the same faults, the outcomes are paired, and the significance of the
+//! difference between the SDC rates is given by McNemar's test on the faults with different outcomes.
+
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use super::fault::Fault;
+use super::sampling::Estimate;
+use crate::{Model, NN};
+
+/// The outcomes of a fault injected in both networks.
+#[derive(Debug, Clone)]
+pub struct ComparedFault {
+    pub fault: Fault,
+    /// True if the output of the first network changed
+    pub a_failed: bool,
+    /// True if the output of the second network changed
+    pub b_failed: bool,
+}
+
+/// Side by side outcomes of the same fault campaign on two networks.
+#[derive(Debug, Clone)]
+pub struct ComparisonReport {
+    /// Seed of the random faults, [None] for a fault list
+    pub seed: Option<u64>,
+    pub outcomes: Vec<ComparedFault>,
+    /// SDC rate of the first network
+    pub a: Estimate,
+    /// SDC rate of the second network
+    pub b: Estimate,
+    /// Time taken by the whole comparison
+    pub duration: Duration,
+}
+
+impl ComparisonReport {
+    pub(crate) fn new(seed: Option<u64>, outcomes: Vec<ComparedFault>, duration: Duration) -> ComparisonReport {
+        let n = outcomes.len() as u128;
+        let a_failures = outcomes.iter().filter(|o| o.a_failed).count() as u128;
+        let b_failures = outcomes.iter().filter(|o| o.b_failed).count() as u128;
+        ComparisonReport {
+            seed,
+            outcomes,
+            a: Estimate::binomial(a_failures, n),
+            b: Estimate::binomial(b_failures, n),
+            duration,
+        }
+    }
+
+    /// Difference between the SDC rates of the second and of the first network: negative if the second
+    /// network is more robust.
+    pub fn sdc_difference(&self) -> f64 {
+        self.b.failure_rate - self.a.failure_rate
+    }
+
+    /// Faults that change the output of the first network only.
+    pub fn only_a_failed(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.a_failed && !o.b_failed).count()
+    }
+
+    /// Faults that change the output of the second network only.
+    pub fn only_b_failed(&self) -> usize {
+        self.outcomes.iter().filter(|o| !o.a_failed && o.b_failed).count()
+    }
+
+    /// Statistic of McNemar's test, as a standard normal deviate: positive if the second network has more SDCs.
+    pub fn z_score(&self) -> f64 {
+        let (only_a, only_b) = (self.only_a_failed() as f64, self.only_b_failed() as f64);
+        if only_a + only_b == 0.0 {
+            0.0
+        } else {
+            (only_b - only_a) / (only_a + only_b).sqrt()
+        }
+    }
+
+    /// Two-sided p-value of McNemar's test: the probability of a difference at least this large if the two
+    /// networks were equally robust.
+    pub fn p_value(&self) -> f64 {
+        erfc(self.z_score().abs() / std::f64::consts::SQRT_2)
+    }
+
+    /// True if the difference between the SDC rates is significant at level `alpha` (e.g. 0.05).
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value() < alpha
+    }
+}
+
+impl Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.seed {
+            Some(seed) => writeln!(f, "Comparison of two SNNs over {} faults (seed {}):", self.outcomes.len(), seed)?,
+            None => writeln!(f, "Comparison of two SNNs over a fault list of {} faults:", self.outcomes.len())?,
+        }
+        writeln!(f, "{:>8} | {:>8} | {:>8}", "", "A", "B")?;
+        writeln!(
+            f,
+            "{:>8} | {:>7.2}% | {:>7.2}%",
+            "Masked",
+            (1.0 - self.a.failure_rate) * 100.0,
+            (1.0 - self.b.failure_rate) * 100.0
+        )?;
+        writeln!(
+            f,
+            "{:>8} | {:>7.2}% | {:>7.2}%",
+            "SDC",
+            self.a.failure_rate * 100.0,
+            self.b.failure_rate * 100.0
+        )?;
+        writeln!(
+            f,
+            "{} faults change only the output of A, {} only the output of B.",
+            self.only_a_failed(),
+            self.only_b_failed()
+        )?;
+        write!(
+            f,
+            "SDC rate difference (B - A): {:+.2}% (z = {:.2}, p = {:.4}), {} at the 5% level.\nThe process took {:?} to be finished.",
+            self.sdc_difference() * 100.0,
+            self.z_score(),
+            self.p_value(),
+            if self.is_significant(0.05) { "significant" } else { "not significant" },
+            self.duration
+        )
+    }
+}
+
+/// Checks that two networks have the same number of layers, of neurons in every layer and of inputs.
+pub fn check_topology<M: Model + Clone>(a: &NN<M>, b: &NN<M>) -> Result<(), String> {
+    if a.layers.len() != b.layers.len() {
+        return Err(format!("The networks have {} and {} layers", a.layers.len(), b.layers.len()));
+    }
+    for (idx, (layer_a, layer_b)) in a.layers.iter().zip(b.layers.iter()).enumerate() {
+        if layer_a.input_weights.shape() != layer_b.input_weights.shape() {
+            return Err(format!(
+                "Layer {} has {:?} and {:?} input weights",
+                idx,
+                layer_a.input_weights.shape(),
+                layer_b.input_weights.shape()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Complementary error function, with a fractional error below 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let poly = -1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let ans = t * (-x * x + poly).exp();
+    if x >= 0.0 {
+        ans
+    } else {
+        2.0 - ans
+    }
+}
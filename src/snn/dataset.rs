@@ -0,0 +1,220 @@
+//! # Datasets
+//!
+//! A fault may be masked for one input and critical for another, so a resilience campaign can evaluate
+//! every fault over a whole dataset of input spike patterns instead of a single one.
+//!
+//! The output of the network is classified by the output neuron with the most spikes (ties go to the
+//! lowest neuron, and an output without spikes is a class of its own). A faulty run misclassifies an
+//! input when its class differs from the one of the fault free network on the same input.
+//!
+//! A dataset is built from a list of inputs or read from a directory of input JSON files, in the same
+//! format of the single input of a campaign.
+
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use super::fault::Fault;
+use super::input_fault::InputFault;
+use super::json_adapter::InputJson;
+
+/// A list of input spike patterns.
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    pub(crate) inputs: Vec<Vec<(u128, Vec<u128>)>>,
+    /// Name of every input, the file it was read from or its position in the list
+    pub(crate) names: Vec<String>,
+}
+
+/// A fault injected over all the inputs of a dataset.
+#[derive(Debug, Clone)]
+pub enum SweepFault {
+    /// A fault of the network
+    Network(Fault),
+    /// A fault of the input encoding, applied to every input
+    Input(InputFault),
+}
+
+/// Outcome of a single fault over all the inputs of a dataset.
+#[derive(Debug, Clone)]
+pub struct FaultSweep {
+    /// The injected fault
+    pub fault: SweepFault,
+    /// Index of every input whose class differs from the one of the fault free network
+    pub misclassified: Vec<usize>,
+}
+
+/// Summary of a resilience test over a dataset.
+///
+/// An input is misclassified when the class of the faulty network differs from the class of the fault free
+/// network on the same input (see [DatasetReport::right_classes]), not from a label of the dataset: the
+/// report measures the effect of the faults, not the accuracy of the network.
+#[derive(Debug, Clone)]
+pub struct DatasetReport {
+    /// Name of every input of the dataset
+    pub inputs: Vec<String>,
+    /// Class of every input for the network without faults
+    pub right_classes: Vec<Option<u128>>,
+    /// Outcome of every injected fault
+    pub faults: Vec<FaultSweep>,
+    /// Time taken by the whole test
+    pub duration: Duration,
+}
+
+impl Dataset {
+    /// Creates a dataset from a list of inputs, named after their position.
+    pub fn new(inputs: Vec<Vec<(u128, Vec<u128>)>>) -> Dataset {
+        let names = (0..inputs.len()).map(|i| i.to_string()).collect();
+        Dataset { inputs, names }
+    }
+
+    /// Reads all the ".json" input files of a directory, in alphabetical order.
+    ///
+    /// # Arguments
+    ///
+    /// * `pathname` - A string slice representing the path to the directory.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Dataset` on success or an error message `String` on failure.
+    pub fn read_from_dir(pathname: &str) -> Result<Dataset, String> {
+        let mut paths: Vec<_> = fs::read_dir(pathname)
+            .map_err(|e| format!("Error reading {}: {}", pathname, e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+            .collect();
+        paths.sort();
+        if paths.is_empty() {
+            return Err(format!("No input files in {}", pathname));
+        }
+
+        let mut dataset = Dataset::default();
+        for path in paths {
+            let content =
+                fs::read_to_string(&path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+            let input = InputJson::parse_input(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+            dataset.push(file_name(&path), input);
+        }
+        Ok(dataset)
+    }
+
+    /// Adds an input to the dataset.
+    pub fn push(&mut self, name: String, input: Vec<(u128, Vec<u128>)>) {
+        self.names.push(name);
+        self.inputs.push(input);
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    pub fn inputs(&self) -> &[Vec<(u128, Vec<u128>)>] {
+        &self.inputs
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+/// Class of an output: the output neuron with the most spikes, [None] if there are no spikes.
+pub fn classify(output: &[(u128, Vec<u128>)]) -> Option<u128> {
+    output
+        .iter()
+        .filter(|(_, spikes)| !spikes.is_empty())
+        .fold(None, |best: Option<(u128, usize)>, (neuron, spikes)| match best {
+            Some((n, count)) if count > spikes.len() || (count == spikes.len() && n < *neuron) => best,
+            _ => Some((*neuron, spikes.len())),
+        })
+        .map(|(neuron, _)| neuron)
+}
+
+impl FaultSweep {
+    /// Fraction of the inputs misclassified with this fault.
+    pub fn misclassification_rate(&self, num_inputs: usize) -> f64 {
+        if num_inputs == 0 {
+            0.0
+        } else {
+            self.misclassified.len() as f64 / num_inputs as f64
+        }
+    }
+}
+
+impl DatasetReport {
+    /// Misclassification rate of every fault, in the order they were injected.
+    pub fn misclassification_rates(&self) -> Vec<f64> {
+        self.faults
+            .iter()
+            .map(|sweep| sweep.misclassification_rate(self.inputs.len()))
+            .collect()
+    }
+
+    /// Misclassification rate averaged over all the faults.
+    pub fn mean_misclassification_rate(&self) -> f64 {
+        if self.faults.is_empty() {
+            0.0
+        } else {
+            self.misclassification_rates().iter().sum::<f64>() / self.faults.len() as f64
+        }
+    }
+
+    /// Number of faults masked for every input of the dataset.
+    pub fn masked_faults(&self) -> usize {
+        self.faults.iter().filter(|sweep| sweep.misclassified.is_empty()).count()
+    }
+
+    /// Number of faults that misclassify some inputs but not all of them.
+    pub fn input_dependent_faults(&self) -> usize {
+        self.faults
+            .iter()
+            .filter(|sweep| !sweep.misclassified.is_empty() && sweep.misclassified.len() < self.inputs.len())
+            .count()
+    }
+}
+
+impl Display for DatasetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "For this SNN, running {} faults over {} inputs, the mean misclassification rate is {:.2}%.\n{} faults are always masked, {} depend on the input.\nThe process took {:?} to be finished.",
+            self.faults.len(),
+            self.inputs.len(),
+            self.mean_misclassification_rate() * 100.0,
+            self.masked_faults(),
+            self.input_dependent_faults(),
+            self.duration
+        )?;
+        for sweep in self.faults.iter() {
+            if sweep.misclassified.is_empty() {
+                continue;
+            }
+            write!(
+                f,
+                "\n{}: {:.2}% of the inputs misclassified",
+                sweep.fault,
+                sweep.misclassification_rate(self.inputs.len()) * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SweepFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepFault::Network(fault) => write!(f, "{}", fault),
+            SweepFault::Input(fault) => write!(f, "{}", fault),
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
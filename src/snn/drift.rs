@@ -0,0 +1,266 @@
+//! # Parameter Drift
+//!
+//! Analog and memristive neuromorphic hardware does not only suffer bit flips: its parameters drift
+//! gradually with aging and temperature. This module perturbs the thresholds (`v_th`), the time
+//! constants (`tau`) and the input weights of a network with two drift models:
+//!
+//! - **Gaussian**: every parameter is multiplied once by `1 + N(0, sigma)`, a random deviation of its
+//!   nominal value;
+//! - **Linear**: every parameter drifts by `rate` times its nominal value per time unit, so at time `t`
+//!   it is `p * (1 + rate * t)`.
+//!
+//! A [DriftSweep] simulates the network with growing drift magnitudes and reports the accuracy-vs-drift
+//! curve: the fraction of the runs whose class (the output neuron with the most spikes) is the same of
+//! the network without drift. The Gaussian deviations are drawn from a random number generator seeded
+//! with the seed of the sweep, so the same seed always gives the same curve.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::drift::{DriftModel, DriftSweep, DriftTarget};
+//!
+//! let sweep = DriftSweep::new(
+//!     DriftModel::Gaussian { sigma: 0.0 },
+//!     vec![DriftTarget::VTh, DriftTarget::Weights],
+//!     vec![0.01, 0.05, 0.1, 0.2],
+//! )
+//! .with_trials(50)
+//! .with_seed(42);
+//! ```
+
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use nalgebra::DMatrix;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::dataset::{classify, Dataset};
+use super::output::SimulationOutput;
+use crate::{Model, NN};
+
+/// How the parameters drift.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DriftModel {
+    /// A random deviation with standard deviation `sigma`, relative to the nominal value
+    Gaussian { sigma: f64 },
+    /// A deviation growing by `rate` times the nominal value per time unit of the simulation
+    Linear { rate: f64 },
+}
+
+/// Parameters hit by the drift.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftTarget {
+    VTh,
+    Tau,
+    Weights,
+}
+
+/// A drift model applied to some parameters of a network.
+#[derive(Debug, Clone)]
+pub struct Drift {
+    pub model: DriftModel,
+    pub targets: Vec<DriftTarget>,
+}
+
+/// A linear drift in progress in a layer, with the nominal values of the drifting parameters.
+#[derive(Debug, Clone)]
+pub(crate) struct LayerDrift {
+    pub(crate) rate: f64,
+    pub(crate) targets: Vec<DriftTarget>,
+    /// Time the parameters were last updated
+    pub(crate) last_ts: u128,
+    pub(crate) v_th: Vec<f64>,
+    pub(crate) tau: Vec<f64>,
+    pub(crate) weights: DMatrix<f64>,
+}
+
+/// A campaign simulating a network with growing drift magnitudes.
+#[derive(Debug, Clone)]
+pub struct DriftSweep {
+    /// The drift model, whose magnitude is replaced by every magnitude of the sweep
+    pub(crate) model: DriftModel,
+    pub(crate) targets: Vec<DriftTarget>,
+    pub(crate) magnitudes: Vec<f64>,
+    /// Number of drifted networks for every magnitude of a Gaussian drift
+    pub(crate) trials: usize,
+    /// Seed of the Gaussian deviations, [None] for a random one
+    pub(crate) seed: Option<u64>,
+}
+
+/// Accuracy of the drifted networks for a drift magnitude.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftPoint {
+    pub magnitude: f64,
+    /// Fraction of the runs with the same class of the network without drift
+    pub accuracy: f64,
+    /// Fraction of the runs with exactly the same output of the network without drift
+    pub same_output: f64,
+}
+
+/// The accuracy-vs-drift curve of a [DriftSweep].
+#[derive(Debug, Clone)]
+pub struct DriftReport {
+    pub model: DriftModel,
+    pub targets: Vec<DriftTarget>,
+    pub points: Vec<DriftPoint>,
+    /// Seed of the Gaussian deviations, [None] for a linear drift
+    pub seed: Option<u64>,
+    /// Time taken by the whole sweep
+    pub duration: Duration,
+}
+
+impl DriftModel {
+    /// The same drift model with another magnitude (`sigma` or `rate`).
+    pub fn with_magnitude(&self, magnitude: f64) -> DriftModel {
+        match self {
+            DriftModel::Gaussian { .. } => DriftModel::Gaussian { sigma: magnitude },
+            DriftModel::Linear { .. } => DriftModel::Linear { rate: magnitude },
+        }
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        match self {
+            DriftModel::Gaussian { sigma } => *sigma,
+            DriftModel::Linear { rate } => *rate,
+        }
+    }
+}
+
+impl Drift {
+    pub fn new(model: DriftModel, targets: Vec<DriftTarget>) -> Drift {
+        Drift { model, targets }
+    }
+
+    pub(crate) fn hits(&self, target: DriftTarget) -> bool {
+        self.targets.contains(&target)
+    }
+}
+
+impl LayerDrift {
+    /// The increment of a parameter with nominal value `nominal` from the last update to `ts`.
+    pub(crate) fn step(&self, nominal: f64, ts: u128) -> f64 {
+        nominal * self.rate * (ts as f64 - self.last_ts as f64)
+    }
+}
+
+impl DriftSweep {
+    /// Creates a sweep over the given magnitudes, with 10 drifted networks for every magnitude of a
+    /// Gaussian drift. A linear drift is deterministic, so it is simulated once for every magnitude.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The drift model, whose magnitude is ignored.
+    /// * `targets` - The drifting parameters.
+    /// * `magnitudes` - The values of `sigma` or `rate` to test.
+    pub fn new(model: DriftModel, targets: Vec<DriftTarget>, magnitudes: Vec<f64>) -> DriftSweep {
+        DriftSweep {
+            model,
+            targets,
+            magnitudes,
+            trials: 10,
+            seed: None,
+        }
+    }
+
+    /// Sets the number of drifted networks for every magnitude of a Gaussian drift. It is ignored by a
+    /// linear drift, which is deterministic.
+    pub fn with_trials(mut self, trials: usize) -> DriftSweep {
+        self.trials = trials.max(1);
+        self
+    }
+
+    /// Sets the seed of the Gaussian deviations, so that the sweep can be repeated.
+    pub fn with_seed(mut self, seed: u64) -> DriftSweep {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Simulates the network with every drift magnitude over all the inputs of a dataset.
+    ///
+    /// # Arguments
+    ///
+    /// * `snn` - The network without drift.
+    /// * `dataset` - The inputs given to the network.
+    ///
+    /// # Returns
+    ///
+    /// The accuracy of the drifted networks for every magnitude.
+    pub fn run<M: Model + Clone>(&self, snn: NN<M>, dataset: &Dataset) -> DriftReport {
+        let time_init = std::time::Instant::now();
+        let right_outputs: Vec<SimulationOutput> = dataset
+            .inputs()
+            .iter()
+            .map(|input| snn.solve_sequential(input.clone()))
+            .collect();
+        let (trials, seed) = match self.model {
+            DriftModel::Gaussian { .. } => (self.trials, Some(self.seed.unwrap_or_else(|| rand::thread_rng().gen()))),
+            DriftModel::Linear { .. } => (1, None),
+        };
+        let mut rng = StdRng::seed_from_u64(seed.unwrap_or_default());
+
+        let points = self
+            .magnitudes
+            .iter()
+            .map(|magnitude| {
+                let drift = Drift::new(self.model.with_magnitude(*magnitude), self.targets.clone());
+                let (mut same_class, mut same_output, mut runs) = (0, 0, 0);
+                for _ in 0..trials {
+                    let mut drifted = snn.clone();
+                    drifted.use_drift_with_rng(&drift, &mut rng);
+                    for (input, right_output) in dataset.inputs().iter().zip(right_outputs.iter()) {
+                        let output = drifted.solve_sequential(input.clone());
+                        same_class += (classify(output.as_slice()) == classify(right_output.as_slice())) as usize;
+                        same_output += (output == *right_output) as usize;
+                        runs += 1;
+                    }
+                }
+                let runs = runs.max(1) as f64;
+                DriftPoint {
+                    magnitude: *magnitude,
+                    accuracy: same_class as f64 / runs,
+                    same_output: same_output as f64 / runs,
+                }
+            })
+            .collect();
+
+        DriftReport {
+            model: self.model,
+            targets: self.targets.clone(),
+            points,
+            seed,
+            duration: std::time::Instant::now() - time_init,
+        }
+    }
+}
+
+impl Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.model {
+            DriftModel::Gaussian { .. } => "sigma",
+            DriftModel::Linear { .. } => "rate",
+        };
+        write!(f, "Accuracy of the SNN with drift of {:?}", self.targets)?;
+        if let Some(seed) = self.seed {
+            write!(f, " (seed {})", seed)?;
+        }
+        write!(f, ":\n{:>10} | accuracy | same output", name)?;
+        for point in self.points.iter() {
+            write!(
+                f,
+                "\n{:>10} | {:>7.2}% | {:>10.2}%",
+                point.magnitude,
+                point.accuracy * 100.0,
+                point.same_output * 100.0
+            )?;
+        }
+        write!(f, "\nThe process took {:?} to be finished.", self.duration)
+    }
+}
+
+/// A sample of the standard normal distribution (Box-Muller transform).
+pub(crate) fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
@@ -0,0 +1,304 @@
+//! # Fault Lists
+//!
+//! A fault list is an explicit set of faults to inject, one per faulty run, instead of faults chosen at
+//! random by the resilience campaign. Every fault hits a given bit of a component of a neuron (or of a
+//! weight) and becomes active at a given time of the simulation.
+//!
+//! Fault lists can be read from a JSON file (an array of objects) or from a CSV file with a header row.
+//! The fields of a fault are:
+//!
+//! - `layer`, `neuron`: the neuron hit by the fault (for a weight, the neuron receiving it);
+//! - `component`: "v_th", "v_rest", "v_reset", "tau", "v_mem", "full adder", "comparator v_mem",
+//!   "comparator v_th", "reset", "reset not applied", "reset twice", "multiplier", "exponential" or "weight";
+//! - `bit`: the index of the faulty bit;
+//! - `stuck`: "zero", "one" or "transient", with the same aliases of the resilience configuration;
+//! - `time`: the timestamp from which the fault is active (0 to inject it before the simulation);
+//! - `from` (only for weights): the neuron of the previous layer sending the weight;
+//! - `link` (optional, only for the full adder and the multiplier): the faulty link of the heap;
+//! - `duration` (optional): "permanent" (default) if the fault lasts until the end of the simulation, or
+//!   "transient" if it lasts a single time step.
+//!
+//! ## Example
+//!
+//! ```text
+//! layer,neuron,component,bit,stuck,time,from,link,duration
+//! 0,1,v_th,62,one,0,,,
+//! 1,0,weight,52,transient,10,2,,transient
+//! 1,2,full adder,30,zero,5,,3,permanent
+//! ```
+
+use std::fmt::{self, Display};
+use std::fs;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use super::model::heap::{adder_dim, adder_links, num_links};
+use super::model::lif::{ComparatorOperand, ResetFault};
+use super::model::Stuck;
+
+/// Component of the network hit by a fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultComponent {
+    VTh,
+    VRest,
+    VReset,
+    Tau,
+    VMem,
+    FullAdder,
+    Comparator(ComparatorOperand),
+    Reset(ResetFault),
+    Multiplier,
+    Exponential,
+    Weight,
+}
+
+impl FaultComponent {
+    /// The number of links of the heap of the full adder or of the multiplier of a neuron with
+    /// `num_inputs` inputs, or [None] if the component has no heap.
+    pub fn num_links(&self, num_inputs: usize) -> Option<usize> {
+        match self {
+            FaultComponent::FullAdder => Some(adder_links(adder_dim(num_inputs))),
+            // two operands: the distance from the rest potential and the leak factor
+            FaultComponent::Multiplier => Some(num_links(2)),
+            _ => None,
+        }
+    }
+}
+
+/// How long a fault lasts once it is active.
+///
+/// This is independent from the [Stuck] type: a bit stuck at 0 or 1, or flipped, may be permanent (a
+/// defect of the hardware) or last a single time step (e.g. a particle strike).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FaultDuration {
+    /// Active until the end of the simulation
+    #[default]
+    Permanent,
+    /// Active during the first time step of the layer at or after the time of the fault, then the
+    /// component is repaired. A membrane potential corrupted during that step is not restored.
+    Transient,
+}
+
+/// A single fault of a fault list.
+#[derive(Debug, Clone)]
+pub struct Fault {
+    pub layer: usize,
+    pub neuron: usize,
+    pub component: FaultComponent,
+    pub bit: usize,
+    pub stuck: Stuck,
+    /// Timestamp from which the fault is active
+    pub time: u128,
+    /// Neuron of the previous layer sending the faulty weight
+    pub from: Option<usize>,
+    /// Faulty link of the heap of the full adder or of the multiplier, random if [None]
+    pub link: Option<usize>,
+    /// How long the fault lasts
+    pub duration: FaultDuration,
+}
+
+/// A row of a fault list file, before its validation.
+#[derive(Debug, Deserialize)]
+struct FaultRecord {
+    layer: usize,
+    neuron: usize,
+    component: String,
+    bit: usize,
+    stuck: String,
+    #[serde(default)]
+    time: u128,
+    #[serde(default)]
+    from: Option<usize>,
+    #[serde(default)]
+    link: Option<usize>,
+    #[serde(default)]
+    duration: Option<String>,
+}
+
+impl Fault {
+    /// Creates a fault active from the start of the simulation.
+    pub fn new(layer: usize, neuron: usize, component: FaultComponent, bit: usize, stuck: Stuck) -> Fault {
+        Fault {
+            layer,
+            neuron,
+            component,
+            bit,
+            stuck,
+            time: 0,
+            from: None,
+            link: None,
+            duration: FaultDuration::Permanent,
+        }
+    }
+
+    /// Sets the timestamp from which the fault is active.
+    pub fn at(mut self, time: u128) -> Fault {
+        self.time = time;
+        self
+    }
+
+    /// Sets the neuron of the previous layer sending the faulty weight.
+    pub fn from(mut self, from: usize) -> Fault {
+        self.from = Some(from);
+        self
+    }
+
+    /// Sets the faulty link of the heap of the full adder or of the multiplier.
+    pub fn link(mut self, link: usize) -> Fault {
+        self.link = Some(link);
+        self
+    }
+
+    /// Sets how long the fault lasts.
+    pub fn lasting(mut self, duration: FaultDuration) -> Fault {
+        self.duration = duration;
+        self
+    }
+
+    /// Reads a fault list from a file. Files ending in ".csv" are read as CSV, all the others as JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `pathname` - A string slice representing the path to the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the faults on success or an error message `String` on failure.
+    pub fn read_from_file(pathname: &str) -> Result<Vec<Fault>, String> {
+        let content = fs::read_to_string(pathname).map_err(|e| format!("Error reading {}: {}", pathname, e))?;
+        if pathname.to_lowercase().ends_with(".csv") {
+            Self::from_csv(&content)
+        } else {
+            Self::from_json(&content)
+        }
+    }
+
+    /// Parses a fault list from a JSON array of faults.
+    pub fn from_json(content: &str) -> Result<Vec<Fault>, String> {
+        let records: Vec<FaultRecord> = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        records.into_iter().map(FaultRecord::into_fault).collect()
+    }
+
+    /// Parses a fault list from a CSV text with a header row. Empty lines are ignored, and the
+    /// optional columns may be missing or empty.
+    pub fn from_csv(content: &str) -> Result<Vec<Fault>, String> {
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        let header: Vec<String> = match lines.next() {
+            Some(header) => header.split(',').map(|c| c.trim().to_lowercase()).collect(),
+            None => return Ok(vec![]),
+        };
+
+        lines
+            .enumerate()
+            .map(|(row, line)| {
+                let values: Vec<&str> = line.split(',').map(|v| v.trim()).collect();
+                let field = |name: &str| -> Option<&str> {
+                    header
+                        .iter()
+                        .position(|c| c == name)
+                        .and_then(|i| values.get(i).copied())
+                        .filter(|v| !v.is_empty())
+                };
+                let required = |name: &str| field(name).ok_or(format!("Row {}: missing {}", row + 1, name));
+                let number = |name: &str, v: &str| {
+                    v.parse::<u128>().map_err(|_| format!("Row {}: invalid {}: {}", row + 1, name, v))
+                };
+                let optional = |name: &str| field(name).map(|v| number(name, v).map(|n| n as usize)).transpose();
+
+                FaultRecord {
+                    layer: number("layer", required("layer")?)? as usize,
+                    neuron: number("neuron", required("neuron")?)? as usize,
+                    component: required("component")?.to_string(),
+                    bit: number("bit", required("bit")?)? as usize,
+                    stuck: required("stuck")?.to_string(),
+                    time: field("time").map(|v| number("time", v)).transpose()?.unwrap_or(0),
+                    from: optional("from")?,
+                    link: optional("link")?,
+                    duration: field("duration").map(str::to_string),
+                }
+                .into_fault()
+            })
+            .collect()
+    }
+}
+
+impl FaultRecord {
+    fn into_fault(self) -> Result<Fault, String> {
+        let component = FaultComponent::from_str(&self.component)?;
+        if component == FaultComponent::Weight && self.from.is_none() {
+            return Err(format!(
+                "The weight fault of neuron {} of layer {} has no \"from\" neuron",
+                self.neuron, self.layer
+            ));
+        }
+        Ok(Fault {
+            layer: self.layer,
+            neuron: self.neuron,
+            component,
+            bit: self.bit,
+            stuck: Stuck::from_name(&self.stuck)?,
+            time: self.time,
+            from: self.from,
+            link: self.link,
+            duration: self.duration.as_deref().map(FaultDuration::from_str).transpose()?.unwrap_or_default(),
+        })
+    }
+}
+
+impl FromStr for FaultComponent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "v_th" | "vth" | "threshold" => Ok(FaultComponent::VTh),
+            "v_rest" | "vrest" => Ok(FaultComponent::VRest),
+            "v_reset" | "vreset" => Ok(FaultComponent::VReset),
+            "tau" | "v_tau" => Ok(FaultComponent::Tau),
+            "v_mem" | "vmem" | "membrane potential" => Ok(FaultComponent::VMem),
+            "full adder" | "fulladder" | "full_adder" | "adder" => Ok(FaultComponent::FullAdder),
+            "comparator v_mem" | "comparator_v_mem" => Ok(FaultComponent::Comparator(ComparatorOperand::VMem)),
+            "comparator v_th" | "comparator_v_th" => Ok(FaultComponent::Comparator(ComparatorOperand::VTh)),
+            "reset" | "reset corrupted" => Ok(FaultComponent::Reset(ResetFault::Corrupted)),
+            "reset not applied" => Ok(FaultComponent::Reset(ResetFault::NotApplied)),
+            "reset twice" | "reset applied twice" => Ok(FaultComponent::Reset(ResetFault::AppliedTwice)),
+            "multiplier" | "mul" => Ok(FaultComponent::Multiplier),
+            "exponential" | "exp" | "exp unit" => Ok(FaultComponent::Exponential),
+            "weight" | "weights" | "w" | "synapse" => Ok(FaultComponent::Weight),
+            _ => Err(format!("Invalid fault component: {}", s)),
+        }
+    }
+}
+
+impl FromStr for FaultDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "permanent" | "p" => Ok(FaultDuration::Permanent),
+            "transient" | "one-shot" | "one shot" | "t" => Ok(FaultDuration::Transient),
+            _ => Err(format!("Invalid fault duration: {}", s)),
+        }
+    }
+}
+
+impl Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} bit {} stuck {:?} in neuron {} of layer {}",
+            self.component, self.bit, self.stuck, self.neuron, self.layer
+        )?;
+        if let Some(from) = self.from {
+            write!(f, " (from neuron {})", from)?;
+        }
+        if let Some(link) = self.link {
+            write!(f, " (link {})", link)?;
+        }
+        write!(f, " at time {}", self.time)?;
+        if self.duration == FaultDuration::Transient {
+            write!(f, " for one step")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+//! # Input Encoding Faults
+//!
+//! The input spike trains given to the network are produced by a sensor or an encoder, which can be
+//! faulty too. This module corrupts the input before the simulation, to compare the vulnerability of the
+//! front end with the one of the network:
+//!
+//! - **timestamp**: a bit of the `u128` timestamp of an input spike is stuck or flipped, moving the spike;
+//! - **dropped event**: an input spike is lost;
+//! - **stuck-on**: an input neuron spikes at every time step of the input;
+//! - **stuck-off**: an input neuron never spikes.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::input_fault::InputFault;
+//! use spiking_nn_resilience::snn::model::Stuck;
+//!
+//! let input = vec![(0, vec![1, 2, 5]), (1, vec![3])];
+//! let fault = InputFault::Timestamp { neuron: 0, spike: 1, bit: 3, stuck: Stuck::One };
+//! assert_eq!(fault.apply(&input).unwrap(), vec![(0, vec![1, 5, 10]), (1, vec![3])]);
+//! assert_eq!(InputFault::StuckOn { neuron: 1 }.apply(&input).unwrap()[1], (1, vec![1, 2, 3, 4, 5]));
+//! ```
+
+use std::fmt::{self, Display};
+
+use rand::Rng;
+
+use super::model::{apply_stuck, Stuck};
+
+/// Bits of the timestamp of an input spike.
+pub const TIMESTAMP_BITS: usize = 128;
+
+/// Component of the input encoding hit by a fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputComponent {
+    Timestamp,
+    DroppedEvent,
+    StuckOn,
+    StuckOff,
+}
+
+/// A fault of the input encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputFault {
+    /// A bit of the timestamp of the `spike`-th spike of an input neuron
+    Timestamp {
+        neuron: u128,
+        spike: usize,
+        bit: usize,
+        stuck: Stuck,
+    },
+    /// The `spike`-th spike of an input neuron is lost
+    DroppedEvent { neuron: u128, spike: usize },
+    /// The input neuron spikes at every time step, from the first to the last timestamp of the input
+    StuckOn { neuron: u128 },
+    /// The input neuron never spikes
+    StuckOff { neuron: u128 },
+}
+
+impl InputComponent {
+    /// All the components of the input encoding.
+    pub fn all() -> Vec<InputComponent> {
+        vec![
+            InputComponent::Timestamp,
+            InputComponent::DroppedEvent,
+            InputComponent::StuckOn,
+            InputComponent::StuckOff,
+        ]
+    }
+
+    /// The components of the input encoding of a component of the resilience test, [None] if it is not
+    /// part of the input encoding.
+    pub fn from_name(name: &str) -> Option<Vec<InputComponent>> {
+        match name.to_lowercase().as_str() {
+            "input" | "inputs" | "encoder" | "encoding" | "sensor" => Some(InputComponent::all()),
+            "input timestamp" | "timestamp" | "timestamps" | "ts" => Some(vec![InputComponent::Timestamp]),
+            "dropped event" | "dropped events" | "input drop" | "drop" => Some(vec![InputComponent::DroppedEvent]),
+            "input stuck on" | "stuck on" | "stuck-on" => Some(vec![InputComponent::StuckOn]),
+            "input stuck off" | "stuck off" | "stuck-off" => Some(vec![InputComponent::StuckOff]),
+            _ => None,
+        }
+    }
+}
+
+impl InputFault {
+    /// Chooses a random fault of a component of the input encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `component` - The faulty component.
+    /// * `input` - The input spikes, as pairs of input neuron and spike times.
+    /// * `stuck` - The stuck type of the bit of a timestamp.
+    ///
+    /// # Returns
+    ///
+    /// The fault, or [None] if the input has no neurons, or no spikes for a timestamp or dropped event.
+    pub fn random(component: InputComponent, input: &[(u128, Vec<u128>)], stuck: &Stuck) -> Option<InputFault> {
+        let mut rng = rand::thread_rng();
+        if input.is_empty() {
+            return None;
+        }
+        let neuron = input[rng.gen_range(0..input.len())].0;
+        // a random spike of the whole input, so that every spike is equally likely
+        let mut random_spike = || {
+            let spikes: Vec<(u128, usize)> = input
+                .iter()
+                .flat_map(|(neuron, spikes)| (0..spikes.len()).map(move |i| (*neuron, i)))
+                .collect();
+            (!spikes.is_empty()).then(|| spikes[rng.gen_range(0..spikes.len())])
+        };
+        match component {
+            InputComponent::Timestamp => random_spike().map(|(neuron, spike)| InputFault::Timestamp {
+                neuron,
+                spike,
+                bit: rand::thread_rng().gen_range(0..TIMESTAMP_BITS),
+                stuck: stuck.clone(),
+            }),
+            InputComponent::DroppedEvent => {
+                random_spike().map(|(neuron, spike)| InputFault::DroppedEvent { neuron, spike })
+            }
+            InputComponent::StuckOn => Some(InputFault::StuckOn { neuron }),
+            InputComponent::StuckOff => Some(InputFault::StuckOff { neuron }),
+        }
+    }
+
+    /// The component of the input encoding hit by the fault.
+    pub fn component(&self) -> InputComponent {
+        match self {
+            InputFault::Timestamp { .. } => InputComponent::Timestamp,
+            InputFault::DroppedEvent { .. } => InputComponent::DroppedEvent,
+            InputFault::StuckOn { .. } => InputComponent::StuckOn,
+            InputFault::StuckOff { .. } => InputComponent::StuckOff,
+        }
+    }
+
+    /// Returns the input as produced by the faulty encoder. The spikes of every neuron stay sorted,
+    /// and two spikes of a neuron moved to the same time are merged.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The fault free input spikes, as pairs of input neuron and spike times.
+    ///
+    /// # Returns
+    ///
+    /// The faulty input, or an error if the neuron or the spike of the fault are not in the input.
+    pub fn apply(&self, input: &[(u128, Vec<u128>)]) -> Result<Vec<(u128, Vec<u128>)>, String> {
+        let mut faulty = input.to_vec();
+        let neuron = match self {
+            InputFault::Timestamp { neuron, .. }
+            | InputFault::DroppedEvent { neuron, .. }
+            | InputFault::StuckOn { neuron }
+            | InputFault::StuckOff { neuron } => *neuron,
+        };
+        let spikes = faulty
+            .iter_mut()
+            .find(|(n, _)| *n == neuron)
+            .map(|(_, spikes)| spikes)
+            .ok_or(format!("No input neuron {}", neuron))?;
+        let no_spike = |spike: usize| format!("No spike {} of the input neuron {}", spike, neuron);
+
+        match self {
+            InputFault::Timestamp { spike, bit, stuck, .. } => {
+                if *bit >= TIMESTAMP_BITS {
+                    return Err(format!("Bit {} out of range of a {} bits timestamp", bit, TIMESTAMP_BITS));
+                }
+                let ts = spikes.get_mut(*spike).ok_or(no_spike(*spike))?;
+                *ts = apply_stuck(*ts, stuck, *bit as u64);
+                spikes.sort();
+                spikes.dedup();
+            }
+            InputFault::DroppedEvent { spike, .. } => {
+                if *spike >= spikes.len() {
+                    return Err(no_spike(*spike));
+                }
+                spikes.remove(*spike);
+            }
+            InputFault::StuckOn { .. } => {
+                let all = input.iter().flat_map(|(_, spikes)| spikes.iter().copied());
+                *spikes = match (all.clone().min(), all.max()) {
+                    (Some(first), Some(last)) => (first..=last).collect(),
+                    _ => vec![],
+                };
+            }
+            InputFault::StuckOff { .. } => spikes.clear(),
+        }
+        Ok(faulty)
+    }
+}
+
+impl Display for InputFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputFault::Timestamp { neuron, spike, bit, stuck } => write!(
+                f,
+                "Timestamp bit {} stuck {:?} of spike {} of input neuron {}",
+                bit, stuck, spike, neuron
+            ),
+            InputFault::DroppedEvent { neuron, spike } => {
+                write!(f, "Dropped spike {} of input neuron {}", spike, neuron)
+            }
+            InputFault::StuckOn { neuron } => write!(f, "Input neuron {} stuck on", neuron),
+            InputFault::StuckOff { neuron } => write!(f, "Input neuron {} stuck off", neuron),
+        }
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! This module defines several structs representing data structures commonly used when
 //! reading from or writing to JSON files.
+use std::collections::HashMap;
 use std::fs::File;
 use std::{fs};
 use std::io::{ErrorKind, Write};
@@ -10,6 +11,10 @@ use crate::NN;
 
 use serde::Deserialize;
 
+use crate::snn::mitigation::{Range, RangeAction, RangeCheck, RangeCheckConfig};
+use crate::snn::fault::Fault;
+use crate::snn::model::precision::BitField;
+use crate::snn::sampling::{Allocation, ImportanceWeights, Sampling, StratifyBy};
 use crate::snn::model::Stuck;
 use crate::snn::resilience::Resilience;
 use nalgebra::DMatrix;
@@ -44,6 +49,16 @@ pub struct LayerWeightsJson {
     //layer: u32,
     input_weights: WeightJson,
     intra_weights: WeightJson,
+    /// Delays of the input synapses, missing if all of them are 1
+    delays: Option<DelayJson>,
+}
+
+/// Represents the delays of the input synapses of a layer read from a JSON file, in time steps.
+#[derive(Debug, Deserialize)]
+pub struct DelayJson {
+    rows: usize,
+    cols: usize,
+    data: Vec<u128>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -114,6 +129,20 @@ impl InputJson {
 
         return input_vec;
     }
+
+    /// Parses an input from a JSON array of input neurons and their spikes.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The JSON text.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the input on success or an error message `String` on failure.
+    pub fn parse_input(content: &str) -> Result<Vec<(u128, Vec<u128>)>, String> {
+        let input_json_vec: Vec<InputJson> = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        Ok(input_json_vec.into_iter().map(|i| (i.neuron, i.spikes)).collect())
+    }
 }
 
 impl LayerWeightsJson {
@@ -281,6 +310,12 @@ impl NeuronJson {
                         ),
                     )
                     .unwrap();
+                if let Some(delays) = &current_layer_weights.delays {
+                    nn.use_delays(
+                        current_layer,
+                        DMatrix::from_vec(delays.rows, delays.cols, delays.data.clone()),
+                    )?;
+                }
 
                 current_layer += 1;
                 layer_neurons.clear();
@@ -310,6 +345,12 @@ impl NeuronJson {
                     ),
                 )
                 .expect("Error in layer");
+            if let Some(delays) = &current_layer_weights.delays {
+                nn.use_delays(
+                    current_layer,
+                    DMatrix::from_vec(delays.rows, delays.cols, delays.data.clone()),
+                )?;
+            }
         } else {
             return Err("error".to_string());
         }
@@ -322,6 +363,119 @@ pub struct ResilienceJson {
     components: Vec<String>,
     stuck: String,
     times: u32,
+    /// Number of faulty bits in the ECC codeword of parameters and weights. Missing if ECC is disabled.
+    #[serde(default)]
+    ecc: Option<usize>,
+    /// Range check mitigation to compare with. Missing if the mitigation is disabled.
+    #[serde(default)]
+    range_check: Option<RangeCheckJson>,
+    /// Path of a JSON or CSV fault list to inject instead of the random faults. Missing if the faults are random.
+    #[serde(default)]
+    faults: Option<String>,
+    /// How the random faults are chosen. Missing for uniform sampling.
+    #[serde(default)]
+    sampling: Option<SamplingJson>,
+    /// "sequential", "threaded", "event-driven" or "clock-driven:dt". Missing for the sequential simulation.
+    #[serde(default)]
+    simulation: Option<String>,
+}
+
+/// Represents the sampling of the fault space read from a JSON file.
+#[derive(Debug, Deserialize)]
+pub struct SamplingJson {
+    /// "uniform", "stratified" or "importance"
+    method: String,
+    /// Criteria of the strata: "layer", "component" and/or "bit field"
+    #[serde(default)]
+    by: Vec<String>,
+    /// "equal" or "proportional" (default)
+    #[serde(default)]
+    allocation: Option<String>,
+    /// Importance weights of the "sign", "exponent" and "mantissa" bit fields
+    #[serde(default)]
+    weights: HashMap<String, f64>,
+}
+
+impl SamplingJson {
+    /// Converts `SamplingJson` into `Sampling`.
+    pub fn to_sampling(self) -> Result<Sampling, String> {
+        match self.method.to_lowercase().as_str() {
+            "uniform" | "u" => Ok(Sampling::Uniform),
+            "stratified" | "s" => {
+                let by = self
+                    .by
+                    .iter()
+                    .map(|by| match by.to_lowercase().as_str() {
+                        "layer" | "layers" => Ok(StratifyBy::Layer),
+                        "component" | "components" => Ok(StratifyBy::Component),
+                        "bit field" | "bit_field" | "bitfield" | "field" => Ok(StratifyBy::BitField),
+                        _ => Err(format!("Invalid stratification: {}", by)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let allocation = match self.allocation.as_deref().map(str::to_lowercase).as_deref() {
+                    Some("equal") => Allocation::Equal,
+                    Some("proportional") | None => Allocation::Proportional,
+                    Some(allocation) => return Err(format!("Invalid allocation: {}", allocation)),
+                };
+                Ok(Sampling::Stratified { by, allocation })
+            }
+            "importance" | "i" => {
+                let mut weights = ImportanceWeights::default();
+                for (field, weight) in self.weights {
+                    let field = match field.to_lowercase().as_str() {
+                        "sign" => BitField::Sign,
+                        "exponent" => BitField::Exponent,
+                        "mantissa" => BitField::Mantissa,
+                        _ => return Err(format!("Invalid bit field: {}", field)),
+                    };
+                    weights = weights.with_bit_field(field, weight)?;
+                }
+                Ok(Sampling::Importance(weights))
+            }
+            _ => Err(format!("Invalid sampling method: {}", self.method)),
+        }
+    }
+}
+
+/// Represents the configuration of the range check mitigation read from a JSON file.
+///
+/// If all the ranges (`[min, max]`) are given they are used as they are, otherwise they are learned
+/// from the fault free run and widened by `margin`.
+#[derive(Debug, Deserialize)]
+pub struct RangeCheckJson {
+    /// "clamp" or "reset"
+    action: String,
+    #[serde(default)]
+    margin: Option<f64>,
+    #[serde(default)]
+    v_mem: Option<[f64; 2]>,
+    #[serde(default)]
+    v_th: Option<[f64; 2]>,
+    #[serde(default)]
+    weights: Option<[f64; 2]>,
+}
+
+impl RangeCheckJson {
+    /// Converts `RangeCheckJson` into `RangeCheckConfig`.
+    pub fn to_range_check_config(self) -> Result<RangeCheckConfig, String> {
+        let action = match self.action.to_lowercase().as_str() {
+            "clamp" | "c" => RangeAction::Clamp,
+            "reset" | "r" => RangeAction::Reset,
+            _ => return Err(format!("Invalid range check action: {}", self.action)),
+        };
+        match (self.v_mem, self.v_th, self.weights) {
+            (Some(v_mem), Some(v_th), Some(weights)) => Ok(RangeCheckConfig::Explicit(RangeCheck::new(
+                Range::new(v_mem[0], v_mem[1]),
+                Range::new(v_th[0], v_th[1]),
+                Range::new(weights[0], weights[1]),
+                action,
+            ))),
+            _ => Ok(RangeCheckConfig::Learned {
+                margin: self.margin.unwrap_or(0.1),
+                action,
+            }),
+        }
+    }
 }
 impl ResilienceJson {
     /// Reads the resilience configuration from a JSON file and returns a `Result`.
@@ -348,17 +502,17 @@ impl ResilienceJson {
     /// A `Result` containing `Resilience` on success or an error message `String` on failure.
 
     pub fn to_resilience(self) -> Result<Resilience, String> {
-        let stuck_type = match self.stuck.to_lowercase().as_str() {
-            "stuck_at_0" | "zero" | "z" | "0" => Ok(Stuck::Zero),
-            "stuck_at_1" | "one" | "o" | "1" => Ok(Stuck::One),
-            "transient_bit" | "transient" | "t" | "2" => Ok(Stuck::Transient),
-            _ => Err(format!("Invalid stuck type: {}", self.stuck)),
-        }?;
+        let stuck_type = Stuck::from_name(&self.stuck)?;
 
         Ok(Resilience {
             components: self.components,
             stuck_type,
             times: self.times as u128,
+            ecc: self.ecc,
+            range_check: self.range_check.map(|r| r.to_range_check_config()).transpose()?,
+            faults: self.faults.map(|path| Fault::read_from_file(&path)).transpose()?,
+            sampling: self.sampling.map(|s| s.to_sampling()).transpose()?.unwrap_or_default(),
+            simulation: self.simulation.map(|s| s.parse()).transpose()?.unwrap_or_default(),
         })
     }
 }
@@ -371,7 +525,23 @@ pub struct PathsJson {
     pub input_spikes: String,
     pub layers: String,
     pub resilience: String,
-    pub weights: String
+    pub weights: String,
+    /// Directory of input files to sweep the faults over, missing to use only `input_spikes`
+    #[serde(default)]
+    pub dataset: Option<String>,
+    /// Second network to compare the resilience with, missing to test only one network
+    #[serde(default)]
+    pub compare: Option<ComparePathsJson>,
+}
+
+/// Represents the paths of the second network of a comparison, and the seed of the faults.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ComparePathsJson {
+    pub configurations: String,
+    pub layers: String,
+    pub weights: String,
+    #[serde(default)]
+    pub seed: u64,
 }
 
 impl PathsJson {
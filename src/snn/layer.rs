@@ -5,7 +5,17 @@ use crate::Model;
 use na::DMatrix;
 use nalgebra::DVector;
 
+use rand::Rng;
+
+use super::drift::{standard_normal, Drift, DriftModel, DriftTarget, LayerDrift};
+use super::fault::{Fault, FaultComponent, FaultDuration};
+use super::mitigation::RangeCheck;
+use super::model::ecc::{EccOutcome, EccWord};
+use super::model::fixed::QFormat;
+use super::model::precision::{Precision, WordLayout};
 use super::model::Stuck;
+use super::probe::ProbeTrace;
+use super::recorder::SpikeRecorder;
 
 /// A single layer in the neural network
 ///
@@ -19,6 +29,31 @@ pub struct Layer<M: Model + Clone + 'static> {
     pub(crate) input_weights: DMatrix<f64>,
     /// Square matrix of the intra-layer weights
     pub(crate) intra_weights: DMatrix<f64>,
+    /// Delays in time steps of the input synapses, as the input weights. [None] if all of them are 1
+    pub(crate) delays: Option<DMatrix<u128>>,
+    /// Range checker applied to the weights when they are read, if any
+    pub(crate) range_check: Option<RangeCheck>,
+    /// Fixed point format of the stored weights, [None] if they are stored as `f64`
+    pub(crate) weight_format: Option<QFormat>,
+    /// Numeric precision of the weights and of the sums of the weighted inputs
+    pub(crate) precision: Precision,
+    /// Faults of a fault list not active yet, injected when the simulation reaches their time
+    pub(crate) faults: Vec<Fault>,
+    /// Transient faults active in the current time step, with the time step and the fault free component
+    pub(crate) transients: Vec<(Fault, u128, FaultFree<M::Neuron>)>,
+    /// Linear drift of the parameters in progress, if any
+    pub(crate) drift: Option<LayerDrift>,
+    /// Recorder of the spikes fired by the layer, if any
+    pub(crate) recorder: Option<SpikeRecorder>,
+    /// Traces of the probed neurons of the layer, sampled during a simulation
+    pub(crate) probes: Vec<ProbeTrace>,
+}
+
+/// The fault free state of the component hit by a transient fault, restored at the end of its time step.
+#[derive(Clone)]
+pub(crate) enum FaultFree<N> {
+    Weight(f64),
+    Neuron(N),
 }
 
 impl<M: Model + Clone + 'static> Layer<M> {
@@ -52,6 +87,39 @@ impl<M: Model + Clone + 'static> Layer<M> {
     pub fn get_intra_weight_mut(&mut self, from: usize, to: usize) -> Option<&mut f64> {
         self.intra_weights.get_mut((from, to))
     }
+    /// Get the delay of the input synapse from and to the specified neurons, or [None] if any index is out of bounds.
+    pub fn get_delay(&self, from: usize, to: usize) -> Option<u128> {
+        match &self.delays {
+            Some(delays) => delays.get((from, to)).copied(),
+            None => self.input_weights.get((from, to)).map(|_| 1),
+        }
+    }
+
+    /// The distinct delays of the input synapses from a neuron, sorted: a spike of the neuron reaches the
+    /// layer once for each of them.
+    pub(crate) fn delays_from(&self, from: usize) -> Vec<u128> {
+        match &self.delays {
+            Some(delays) => {
+                let mut from_delays: Vec<u128> = delays.row(from).iter().copied().collect();
+                from_delays.sort();
+                from_delays.dedup();
+                from_delays
+            }
+            None => vec![1],
+        }
+    }
+
+    /// True if a spike reaches a neuron at time `ts`, through a synapse with the delay of the time steps
+    /// passed since the spike, each one `dt` long. The time of the spike is the one it would reach the
+    /// layer with a delay of 1.
+    pub(crate) fn receives(&self, spike: &Spike, neuron_idx: usize, ts: u128, dt: u128) -> bool {
+        match &self.delays {
+            Some(delays) => {
+                ts >= spike.ts && delays[(spike.neuron_id, neuron_idx)] == (ts - spike.ts) / dt.max(1) + 1
+            }
+            None => true,
+        }
+    }
 
     /// Returns an ordered iterator over all the neurons in this layer.
     pub fn iter_neurons(&self) -> <&Vec<M::Neuron> as IntoIterator>::IntoIter {
@@ -128,14 +196,53 @@ impl<M: Model + Clone + 'static> Layer<M> {
 
         let inputs_to_sum: Vec<f64> = input_spike_tmp
             .into_iter()
-            .map(|spike| self.intra_weights[(spike.neuron_id, neuron_idx as usize)])
+            .map(|spike| self.read_weight(self.intra_weights[(spike.neuron_id, neuron_idx as usize)]))
             .collect();
 
         if let Some(mut heap_vec) = M::get_heap(neuron) {
             heap_vec.sum_all(&inputs_to_sum)
         } else {
-            inputs_to_sum.iter().sum()
+            self.precision.sum(&inputs_to_sum)
+        }
+    }
+
+    /// Returns the weight as seen by the neuron, after the range check if enabled.
+    pub fn read_weight(&self, weight: f64) -> f64 {
+        match &self.range_check {
+            Some(range_check) => range_check.check_weight(weight),
+            None => weight,
+        }
+    }
+
+    /// Stores the weights of the layer as fixed point words of the given format.
+    ///
+    /// The weights are rounded to the nearest representable value, and the faults injected in them hit
+    /// a bit of the fixed point word instead of the `f64` one.
+    pub fn use_fixed_weights(&mut self, format: QFormat) {
+        self.input_weights.apply(|w| *w = format.round(*w));
+        self.intra_weights.apply(|w| *w = format.round(*w));
+        self.weight_format = Some(format);
+    }
+
+    /// Sets the numeric precision of the weights and of all the neurons of the layer.
+    ///
+    /// The weights and the parameters of the neurons are rounded to the nearest representable value, and
+    /// the faults injected in them hit a bit of the reduced precision word.
+    pub fn use_precision(&mut self, precision: Precision) {
+        self.input_weights.apply(|w| *w = precision.round(*w));
+        self.intra_weights.apply(|w| *w = precision.round(*w));
+        for neuron in self.neurons.iter_mut() {
+            M::use_precision(neuron, precision);
+        }
+        self.precision = precision;
+    }
+
+    /// Enables (or disables, with [None]) the range check on the weights and on all the neurons of the layer.
+    pub fn use_range_check(&mut self, range_check: Option<RangeCheck>) {
+        for neuron in self.neurons.iter_mut() {
+            M::use_range_check(neuron, range_check.clone());
         }
+        self.range_check = range_check;
     }
 
     /// Applies a stuck bit to a specific neuron's parameter.
@@ -151,7 +258,10 @@ impl<M: Model + Clone + 'static> Layer<M> {
     ///   - "v_tau": Tau (time constant) parameter.
     ///   - "v_mem": Membrane potential parameter with fault injection.
     ///   - "full adder": Logic operation for a full adder.
-    ///   - "comparator": Logic operation for a comparator.
+    ///   - "comparator": A bit of the `v_mem` or `v_th` operand of the comparator.
+    ///   - "reset": Logic resetting the membrane potential after a spike.
+    ///   - "multiplier": Multiplier computing the leak of the membrane potential.
+    ///   - "exponential": Exponential unit computing the leak factor.
     ///
     pub fn stuck_bit_neuron(&mut self, stuck: Stuck, neuron_id: usize, neuron_data: String) {
         match neuron_data.as_str() {
@@ -187,10 +297,275 @@ impl<M: Model + Clone + 'static> Layer<M> {
             "comparator" => {
                 M::use_comparator(self.get_neuron_mut(neuron_id).unwrap(), stuck);
             }
+            //logic for the reset of the membrane potential
+            "reset" => {
+                M::use_reset(self.get_neuron_mut(neuron_id).unwrap(), stuck);
+            }
+            //logic for the leak datapath
+            "multiplier" => {
+                M::use_multiplier(self.get_neuron_mut(neuron_id).unwrap(), stuck);
+            }
+            "exponential" => {
+                M::use_exp_unit(self.get_neuron_mut(neuron_id).unwrap(), stuck);
+            }
 
             _ => {
                 println!("Error: invalid parameter");
             }
         }
     }
+
+    /// Applies a fault to a parameter of a neuron stored in an ECC protected word.
+    ///
+    /// The word of the parameter, in the precision or fixed point format of the neuron, is encoded with a
+    /// SEC-DED Hamming code, `num_bits` random bits of the codeword are stuck and the value read back from
+    /// the word replaces the parameter of the neuron.
+    ///
+    /// # Arguments
+    ///
+    /// * `stuck` - The type of stuck bit to apply.
+    /// * `neuron_id` - The index of the neuron to apply the stuck bit.
+    /// * `neuron_data` - The parameter of the neuron: "v_th", "v_rest", "v_reset" or "v_tau".
+    /// * `num_bits` - The number of faulty bits in the codeword.
+    ///
+    /// # Returns
+    ///
+    /// The [EccOutcome] of the fault, or [None] if the parameter does not exist.
+    pub fn stuck_bit_neuron_ecc(
+        &mut self,
+        stuck: Stuck,
+        neuron_id: usize,
+        neuron_data: String,
+        num_bits: usize,
+    ) -> Option<EccOutcome> {
+        let neuron = self.get_neuron_mut(neuron_id)?;
+        let word = M::get_parameter_word(neuron, neuron_data.as_str())?;
+        let data_bits = M::word_layout(neuron).num_bits as usize;
+        let (read, outcome) = EccWord::inject_random_word(word, data_bits, &stuck, num_bits);
+        M::set_parameter_word(neuron, neuron_data.as_str(), read);
+        Some(outcome)
+    }
+
+    /// Applies a stuck bit to a random bit of an input weight of the layer. If the weights are stored
+    /// as fixed point words, the bit is chosen between the ones of the word.
+    ///
+    /// # Arguments
+    ///
+    /// * `stuck` - The type of stuck bit to apply.
+    /// * `from` - The index of the neuron of the previous layer (row of the input weights).
+    /// * `to` - The index of the neuron of this layer (column of the input weights).
+    pub fn stuck_bit_weight(&mut self, stuck: Stuck, from: usize, to: usize) {
+        let bit = rand::thread_rng().gen_range(0..self.weight_bits());
+        let _ = self.stuck_bit_weight_at(stuck, from, to, bit);
+    }
+
+    /// Applies a stuck bit at the given index of an input weight of the layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `stuck` - The type of stuck bit to apply.
+    /// * `from` - The index of the neuron of the previous layer (row of the input weights).
+    /// * `to` - The index of the neuron of this layer (column of the input weights).
+    /// * `bit` - The index of the bit, in the fixed point or floating point word storing the weight.
+    ///
+    /// # Returns
+    ///
+    /// An error if any index is out of bounds.
+    pub fn stuck_bit_weight_at(&mut self, stuck: Stuck, from: usize, to: usize, bit: usize) -> Result<(), String> {
+        if bit >= self.weight_bits() {
+            return Err(format!("Bit {} out of range of a {} bits weight", bit, self.weight_bits()));
+        }
+        let weight_format = self.weight_format;
+        let weight = self
+            .input_weights
+            .get_mut((from, to))
+            .ok_or(format!("No weight from neuron {} to neuron {}", from, to))?;
+        *weight = match weight_format {
+            Some(format) => format.to_f64(format.stuck_word(format.quantize(*weight), &stuck, bit)),
+            None => self.precision.stuck_bit(*weight, &stuck, bit),
+        };
+        Ok(())
+    }
+
+    /// Number of bits of the words storing the weights.
+    fn weight_bits(&self) -> usize {
+        self.weight_layout().num_bits as usize
+    }
+
+    /// Layout of the words storing the weights.
+    pub fn weight_layout(&self) -> WordLayout {
+        match self.weight_format {
+            Some(format) => format.layout(),
+            None => self.precision.layout(),
+        }
+    }
+
+    /// Injects a fault of a fault list in this layer, regardless of its time.
+    ///
+    /// # Returns
+    ///
+    /// An error if the neuron, the weight or the bit of the fault do not exist.
+    pub fn inject_fault(&mut self, fault: &Fault) -> Result<(), String> {
+        if fault.component == FaultComponent::Weight {
+            let from = fault.from.ok_or("The weight fault has no \"from\" neuron".to_string())?;
+            return self.stuck_bit_weight_at(fault.stuck.clone(), from, fault.neuron, fault.bit);
+        }
+        let num_inputs = self.input_weights.nrows();
+        let neuron = self
+            .get_neuron_mut(fault.neuron)
+            .ok_or(format!("No neuron {} in layer {}", fault.neuron, fault.layer))?;
+        M::use_fault(neuron, fault, num_inputs)
+    }
+
+    /// Schedules a fault, which is injected when the simulation of the layer reaches its time.
+    pub fn schedule_fault(&mut self, fault: Fault) {
+        self.faults.push(fault);
+    }
+
+    /// Injects the scheduled faults whose time is not after `ts`, and repairs the transient faults
+    /// injected in a previous time step.
+    pub fn activate_faults(&mut self, ts: u128) {
+        let (expired, current): (Vec<_>, Vec<_>) = self.transients.drain(..).partition(|(_, step, _)| *step < ts);
+        self.transients = current;
+        // in reverse order, so that the oldest fault free state is the one left
+        for (fault, _, fault_free) in expired.into_iter().rev() {
+            self.repair_fault(&fault, fault_free);
+        }
+
+        let (active, pending): (Vec<Fault>, Vec<Fault>) = self.faults.drain(..).partition(|f| f.time <= ts);
+        self.faults = pending;
+        for fault in active {
+            let fault_free = match (fault.duration, fault.component) {
+                (FaultDuration::Permanent, _) => None,
+                (FaultDuration::Transient, FaultComponent::Weight) => fault
+                    .from
+                    .and_then(|from| self.input_weights.get((from, fault.neuron)))
+                    .map(|w| FaultFree::Weight(*w)),
+                (FaultDuration::Transient, _) => self.get_neuron(fault.neuron).cloned().map(FaultFree::Neuron),
+            };
+            if let Err(err) = self.inject_fault(&fault) {
+                println!("Error injecting the fault: {}", err);
+                continue;
+            }
+            if let Some(fault_free) = fault_free {
+                self.transients.push((fault, ts, fault_free));
+            }
+        }
+    }
+
+    /// Applies a drift to the thresholds, time constants and input weights of the layer.
+    ///
+    /// A Gaussian drift perturbs them immediately, a linear drift when the simulation advances (see
+    /// [Layer::apply_drift]).
+    pub fn use_drift(&mut self, drift: &Drift) {
+        self.use_drift_with_rng(drift, &mut rand::thread_rng());
+    }
+
+    /// Applies a drift to the layer as [Layer::use_drift], drawing the Gaussian deviations from the given
+    /// random number generator, so that a seeded generator always gives the same deviations.
+    pub fn use_drift_with_rng<R: Rng>(&mut self, drift: &Drift, rng: &mut R) {
+        match drift.model {
+            DriftModel::Gaussian { sigma } => {
+                let mut perturb = |val: f64| val * (1.0 + sigma * standard_normal(rng));
+                for neuron in self.neurons.iter_mut() {
+                    for (target, parameter) in [(DriftTarget::VTh, "v_th"), (DriftTarget::Tau, "v_tau")] {
+                        if let (true, Some(val)) = (drift.hits(target), M::get_parameter(neuron, parameter)) {
+                            M::set_parameter(neuron, parameter, perturb(val));
+                        }
+                    }
+                }
+                if drift.hits(DriftTarget::Weights) {
+                    self.input_weights.apply(|w| *w = perturb(*w));
+                }
+            }
+            DriftModel::Linear { rate } => {
+                let nominal = |parameter: &str| -> Vec<f64> {
+                    self.neurons
+                        .iter()
+                        .map(|neuron| M::get_parameter(neuron, parameter).unwrap_or(0.0))
+                        .collect()
+                };
+                self.drift = Some(LayerDrift {
+                    rate,
+                    targets: drift.targets.clone(),
+                    last_ts: 0,
+                    v_th: nominal("v_th"),
+                    tau: nominal("v_tau"),
+                    weights: self.input_weights.clone(),
+                });
+            }
+        }
+    }
+
+    /// Advances the linear drift of the layer, if any, to the time `ts`. The parameters move by the
+    /// drift since the last update, so the faults injected in them are kept.
+    pub fn apply_drift(&mut self, ts: u128) {
+        let Some(drift) = self.drift.as_mut() else {
+            return;
+        };
+        for (idx, neuron) in self.neurons.iter_mut().enumerate() {
+            for (target, parameter, nominal) in
+                [(DriftTarget::VTh, "v_th", &drift.v_th), (DriftTarget::Tau, "v_tau", &drift.tau)]
+            {
+                if let (true, Some(val)) = (drift.targets.contains(&target), M::get_parameter(neuron, parameter)) {
+                    M::set_parameter(neuron, parameter, val + drift.step(nominal[idx], ts));
+                }
+            }
+        }
+        if drift.targets.contains(&DriftTarget::Weights) {
+            let step = drift.weights.map(|w| drift.step(w, ts));
+            self.input_weights += step;
+        }
+        drift.last_ts = ts;
+    }
+
+    /// Restores the component hit by a transient fault.
+    fn repair_fault(&mut self, fault: &Fault, fault_free: FaultFree<M::Neuron>) {
+        match fault_free {
+            FaultFree::Weight(weight) => {
+                if let Some(w) = fault.from.and_then(|from| self.input_weights.get_mut((from, fault.neuron))) {
+                    *w = weight;
+                }
+            }
+            FaultFree::Neuron(fault_free) => {
+                if let Some(neuron) = self.get_neuron_mut(fault.neuron) {
+                    M::remove_fault(neuron, fault, &fault_free);
+                }
+            }
+        }
+    }
+
+    /// Applies a fault to an input weight of the layer stored in an ECC protected word, in the fixed point
+    /// format or in the precision of the weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `stuck` - The type of stuck bit to apply.
+    /// * `from` - The index of the neuron of the previous layer (row of the input weights).
+    /// * `to` - The index of the neuron of this layer (column of the input weights).
+    /// * `num_bits` - The number of faulty bits in the codeword.
+    ///
+    /// # Returns
+    ///
+    /// The [EccOutcome] of the fault, or [None] if any index is out of bounds.
+    pub fn stuck_bit_weight_ecc(
+        &mut self,
+        stuck: Stuck,
+        from: usize,
+        to: usize,
+        num_bits: usize,
+    ) -> Option<EccOutcome> {
+        let (weight_format, precision, data_bits) = (self.weight_format, self.precision, self.weight_bits());
+        let weight = self.input_weights.get_mut((from, to))?;
+        let word = match weight_format {
+            Some(format) => format.to_word(format.quantize(*weight)),
+            None => precision.to_word(*weight),
+        };
+        let (read, outcome) = EccWord::inject_random_word(word, data_bits, &stuck, num_bits);
+        *weight = match weight_format {
+            Some(format) => format.to_f64(format.from_word(read)),
+            None => precision.from_word(read),
+        };
+        Some(outcome)
+    }
 }
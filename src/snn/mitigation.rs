@@ -0,0 +1,278 @@
+//! # Range Checking Mitigation
+//!
+//! Most of the catastrophic bit flips in a `f64` push the value to absurd magnitudes, or make it NaN.
+//! This module defines an optional anomaly detection layer that checks the membrane potential, the
+//! thresholds and the weights against plausible ranges, and repairs the values outside them.
+//!
+//! The ranges can be given explicitly with [RangeCheck::new], or learned from a fault free run of the
+//! network with [RangeCheck::learn]. Every time a value is repaired a counter is incremented: the counters
+//! are shared by all the clones of a [RangeCheck], so they can be read after the simulation even if
+//! every layer works on its own copy of the neurons.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::mitigation::{Range, RangeAction, RangeCheck};
+//!
+//! let range_check = RangeCheck::new(
+//!     Range::new(-10.0, 10.0),
+//!     Range::new(0.0, 5.0),
+//!     Range::new(-3.0, 3.0),
+//!     RangeAction::Clamp,
+//! );
+//! assert_eq!(range_check.check_weight(1e300), 3.0);
+//! assert_eq!(range_check.stats().weights, 1);
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::{Model, NN};
+
+/// Closed interval of plausible values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// What to do with a value outside its range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeAction {
+    /// Clamp the value to the nearest bound of the range. NaN values are handled as in [RangeAction::Reset].
+    Clamp,
+    /// Replace the value with a safe one: the reset potential for the membrane potential, the upper
+    /// bound of the range for the threshold (the neuron is less likely to fire) and zero for the weights
+    /// (the synapse is disconnected).
+    Reset,
+}
+
+/// How many times the range check repaired a value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MitigationStats {
+    pub v_mem: u64,
+    pub v_th: u64,
+    pub weights: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    v_mem: AtomicU64,
+    v_th: AtomicU64,
+    weights: AtomicU64,
+    /// Minimum and maximum membrane potential seen while learning the ranges
+    observed_v_mem: Mutex<Option<(f64, f64)>>,
+}
+
+/// Range checker for the membrane potential, the thresholds and the weights of a network.
+#[derive(Debug, Clone)]
+pub struct RangeCheck {
+    pub v_mem: Range,
+    pub v_th: Range,
+    pub weights: Range,
+    pub action: RangeAction,
+    /// If true the membrane potentials are recorded instead of being checked
+    learning: bool,
+    counters: Arc<Counters>,
+}
+
+impl Range {
+    pub fn new(min: f64, max: f64) -> Range {
+        Range { min, max }
+    }
+
+    /// Returns true if the value is inside the range (NaN never is).
+    pub fn contains(&self, val: f64) -> bool {
+        val >= self.min && val <= self.max
+    }
+
+    /// Widens the range on both sides by `margin` times its width (or its largest bound in absolute
+    /// value, when the range is a single point).
+    pub fn widen(&self, margin: f64) -> Range {
+        let width = self.max - self.min;
+        let base = if width > 0.0 {
+            width
+        } else {
+            self.min.abs().max(self.max.abs())
+        };
+        Range {
+            min: self.min - margin * base,
+            max: self.max + margin * base,
+        }
+    }
+
+    fn repair(&self, val: f64, action: RangeAction, reset_val: f64) -> f64 {
+        match action {
+            RangeAction::Clamp if !val.is_nan() => val.clamp(self.min, self.max),
+            _ => reset_val,
+        }
+    }
+
+    fn from_values(values: impl Iterator<Item = f64>) -> Option<Range> {
+        values.fold(None, |acc, val| match acc {
+            None => Some(Range::new(val, val)),
+            Some(r) => Some(Range::new(r.min.min(val), r.max.max(val))),
+        })
+    }
+}
+
+impl RangeCheck {
+    /// Creates a range checker with explicit ranges.
+    pub fn new(v_mem: Range, v_th: Range, weights: Range, action: RangeAction) -> RangeCheck {
+        RangeCheck {
+            v_mem,
+            v_th,
+            weights,
+            action,
+            learning: false,
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// Learns the ranges from a fault free run of the network.
+    ///
+    /// The range of the membrane potential is the one seen during the simulation of `input`, the ranges of
+    /// the thresholds and of the weights are the ones of the network parameters. All of them are widened by
+    /// `margin` (see [Range::widen]).
+    ///
+    /// # Arguments
+    ///
+    /// * `nn` - The fault free network.
+    /// * `input` - Vector of tuples (neuron_id, vector of spikes) given as input to the network.
+    /// * `margin` - Relative margin added to the learned ranges.
+    /// * `action` - What to do with the values outside the ranges.
+    pub fn learn<M: Model + Clone>(
+        nn: &NN<M>,
+        input: Vec<(u128, Vec<u128>)>,
+        margin: f64,
+        action: RangeAction,
+    ) -> RangeCheck {
+        let all = Range::new(f64::NEG_INFINITY, f64::INFINITY);
+        let mut observer = RangeCheck::new(all, all, all, action);
+        observer.learning = true;
+
+        let mut nn_tmp = nn.clone();
+        nn_tmp.use_range_check(&observer);
+        nn_tmp.solve_sequential(input);
+
+        let v_mem = observer
+            .counters
+            .observed_v_mem
+            .lock()
+            .unwrap()
+            .map(|(min, max)| Range::new(min, max))
+            .unwrap_or(Range::new(0.0, 0.0));
+        let v_th = Range::from_values(
+            nn.layers
+                .iter()
+                .flat_map(|l| l.iter_neurons().filter_map(|n| M::get_parameter(n, "v_th"))),
+        )
+        .unwrap_or(Range::new(0.0, 0.0));
+        let weights = Range::from_values(
+            nn.layers
+                .iter()
+                .flat_map(|l| l.input_weights.iter().chain(l.intra_weights.iter()).copied()),
+        )
+        .unwrap_or(Range::new(0.0, 0.0));
+
+        RangeCheck::new(
+            v_mem.widen(margin),
+            v_th.widen(margin),
+            weights.widen(margin),
+            action,
+        )
+    }
+
+    /// Returns a copy of this range checker with its own counters set to zero.
+    pub fn fresh(&self) -> RangeCheck {
+        RangeCheck {
+            counters: Arc::new(Counters::default()),
+            ..self.clone()
+        }
+    }
+
+    /// Checks the membrane potential of a neuron, whose reset potential is `v_reset`.
+    pub fn check_v_mem(&self, v_mem: f64, v_reset: f64) -> f64 {
+        if self.learning {
+            let mut observed = self.counters.observed_v_mem.lock().unwrap();
+            *observed = match *observed {
+                None => Some((v_mem, v_mem)),
+                Some((min, max)) => Some((min.min(v_mem), max.max(v_mem))),
+            };
+            return v_mem;
+        }
+        if self.v_mem.contains(v_mem) {
+            return v_mem;
+        }
+        self.counters.v_mem.fetch_add(1, Ordering::Relaxed);
+        let reset_val = if v_reset.is_nan() {
+            self.v_mem.min
+        } else {
+            v_reset.clamp(self.v_mem.min, self.v_mem.max)
+        };
+        self.v_mem.repair(v_mem, self.action, reset_val)
+    }
+
+    /// Checks the threshold potential of a neuron.
+    pub fn check_v_th(&self, v_th: f64) -> f64 {
+        if self.learning || self.v_th.contains(v_th) {
+            return v_th;
+        }
+        self.counters.v_th.fetch_add(1, Ordering::Relaxed);
+        self.v_th.repair(v_th, self.action, self.v_th.max)
+    }
+
+    /// Checks a weight read from the weight matrices.
+    pub fn check_weight(&self, weight: f64) -> f64 {
+        if self.learning || self.weights.contains(weight) {
+            return weight;
+        }
+        self.counters.weights.fetch_add(1, Ordering::Relaxed);
+        let reset_val = 0.0f64.clamp(self.weights.min, self.weights.max);
+        self.weights.repair(weight, self.action, reset_val)
+    }
+
+    /// Returns how many times the values have been repaired so far.
+    pub fn stats(&self) -> MitigationStats {
+        MitigationStats {
+            v_mem: self.counters.v_mem.load(Ordering::Relaxed),
+            v_th: self.counters.v_th.load(Ordering::Relaxed),
+            weights: self.counters.weights.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl MitigationStats {
+    /// Adds the counters of `other` to these ones.
+    pub fn add(&mut self, other: &MitigationStats) {
+        self.v_mem += other.v_mem;
+        self.v_th += other.v_th;
+        self.weights += other.weights;
+    }
+
+    /// Total number of repaired values.
+    pub fn total(&self) -> u64 {
+        self.v_mem + self.v_th + self.weights
+    }
+}
+
+/// How the ranges of the mitigation are obtained in a resilience test.
+#[derive(Debug, Clone)]
+pub enum RangeCheckConfig {
+    /// Use the given ranges.
+    Explicit(RangeCheck),
+    /// Learn the ranges from the fault free run (see [RangeCheck::learn]).
+    Learned { margin: f64, action: RangeAction },
+}
+
+impl RangeCheckConfig {
+    /// Builds the range checker for the given network and input.
+    pub fn build<M: Model + Clone>(&self, nn: &NN<M>, input: Vec<(u128, Vec<u128>)>) -> RangeCheck {
+        match self {
+            RangeCheckConfig::Explicit(range_check) => range_check.fresh(),
+            RangeCheckConfig::Learned { margin, action } => {
+                RangeCheck::learn(nn, input, *margin, *action)
+            }
+        }
+    }
+}
@@ -87,10 +87,23 @@
 
 use std::fmt;
 
+pub mod comparison;
+pub mod dataset;
+pub mod drift;
+pub mod fault;
+pub mod input_fault;
 pub mod layer;
+pub mod mitigation;
 pub mod model;
 pub mod nn;
+pub mod output;
+pub mod probe;
+pub mod quantization;
+pub mod recorder;
 pub mod resilience;
+pub mod sampling;
+pub mod session;
+pub mod stream;
 
 pub mod console_input_nn_creator;
 pub mod json_adapter;
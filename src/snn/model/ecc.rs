@@ -0,0 +1,243 @@
+//! # ECC Protected Storage
+//!
+//! This module models a memory word protected by a SEC-DED (Single Error Correction, Double Error
+//! Detection) Hamming code. It is used to store neuron parameters and synaptic weights, so that the
+//! resilience tests can measure how many of the injected faults are corrected, detected or still
+//! reach the network.
+//!
+//! The data bits of a word are extended with the Hamming check bits and 1 overall parity bit: the 64
+//! bits of an `f64` with 7 check bits, for a codeword of 72 bits, the 32 bits of an `f32` with 6 check
+//! bits, for a codeword of 39 bits, and so on (see [codeword_bits]):
+//! - bit 0 holds the overall parity of the codeword;
+//! - bits in power of two positions (1, 2, 4, ...) hold the Hamming check bits;
+//! - all the other positions hold the data bits, from the least significant one.
+//!
+//! The resilience tests store every value in the word of its precision or fixed point format, so a
+//! reduced precision network has narrower codewords.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::model::ecc::{EccStatus, EccWord};
+//! use spiking_nn_resilience::snn::model::Stuck;
+//!
+//! let mut word = EccWord::encode(&2.5f64);
+//! // A single flipped bit is corrected when the word is read
+//! word.inject(&Stuck::Transient, 17);
+//! let (value, status): (f64, EccStatus) = word.decode();
+//! assert_eq!(value, 2.5);
+//! assert_eq!(status, EccStatus::Corrected);
+//! ```
+
+use rand::seq::index::sample;
+
+use super::{Stuck, ToBits};
+
+/// Number of bits of a protected 64 bit word: 64 data bits, 7 check bits and the overall parity bit.
+pub const CODEWORD_BITS: usize = 72;
+
+/// Result of the decoding of a codeword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EccStatus {
+    /// The syndrome is zero: no error found.
+    Clean,
+    /// A single bit error has been found and corrected.
+    Corrected,
+    /// A double bit error has been found. It can not be corrected, the data bits are returned as they are.
+    Detected,
+}
+
+/// Effect of a fault injected in an ECC protected word, compared with the value originally stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EccOutcome {
+    /// The fault did not change the stored codeword (e.g. stuck at the value the bit already had).
+    NoError,
+    /// The error has been corrected, the value read is the original one.
+    Corrected,
+    /// The error has been detected and reported, but the value read is not reliable.
+    Detected,
+    /// The error was neither corrected nor detected: a wrong value reaches the network.
+    Uncorrected,
+}
+
+/// Counters of the outcomes of the faults injected in ECC protected words during a resilience test.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EccStats {
+    pub no_error: u128,
+    pub corrected: u128,
+    pub detected: u128,
+    pub uncorrected: u128,
+}
+
+/// A word of up to 64 bits stored together with its SEC-DED Hamming code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EccWord {
+    codeword: u128,
+    /// Number of data bits of the word
+    data_bits: usize,
+}
+
+/// Number of bits of the codeword protecting `data_bits` data bits, parity bit included.
+pub fn codeword_bits(data_bits: usize) -> usize {
+    let mut check_bits = 0;
+    while (1 << check_bits) < data_bits + check_bits + 1 {
+        check_bits += 1;
+    }
+    data_bits + check_bits + 1
+}
+
+impl EccWord {
+    /// Encodes a 64 bit value in a new codeword.
+    pub fn encode<T: ToBits<u64>>(value: &T) -> Self {
+        Self::encode_word(value.get_bits(), 64)
+    }
+
+    /// Encodes the lowest `data_bits` bits (at most 64) of a word in a new codeword.
+    pub fn encode_word(data: u64, data_bits: usize) -> Self {
+        let data_bits = data_bits.min(64);
+        let mut word = EccWord { codeword: 0, data_bits };
+
+        // spread the data bits in the non power of two positions
+        let mut data_idx = 0;
+        for pos in 1..word.num_bits() {
+            if !pos.is_power_of_two() {
+                if (data >> data_idx) & 1 == 1 {
+                    word.codeword |= 1u128 << pos;
+                }
+                data_idx += 1;
+            }
+        }
+
+        // the check bits make the syndrome of the codeword equal to zero
+        let syndrome = word.syndrome();
+        for pos in (1..word.num_bits()).filter(|pos| pos.is_power_of_two()) {
+            if syndrome & pos != 0 {
+                word.codeword |= 1u128 << pos;
+            }
+        }
+
+        // the overall parity bit makes the number of ones even
+        if word.codeword.count_ones() % 2 == 1 {
+            word.codeword |= 1;
+        }
+
+        word
+    }
+
+    /// Number of bits of the codeword.
+    pub fn num_bits(&self) -> usize {
+        codeword_bits(self.data_bits)
+    }
+
+    /// Decodes the codeword, correcting a single bit error if present.
+    ///
+    /// # Returns
+    ///
+    /// The stored value together with the [EccStatus] of the decoding. When a double error is
+    /// detected the value is built from the data bits as they are stored.
+    pub fn decode<T: ToBits<u64>>(&self) -> (T, EccStatus) {
+        let (data, status) = self.decode_word();
+        (T::from_bits(data), status)
+    }
+
+    /// Decodes the codeword as [EccWord::decode], returning the data bits in the lowest bits of the word.
+    pub fn decode_word(&self) -> (u64, EccStatus) {
+        let mut word = self.clone();
+        let syndrome = word.syndrome();
+        let parity_error = word.codeword.count_ones() % 2 == 1;
+
+        let status = match (syndrome, parity_error) {
+            (0, false) => EccStatus::Clean,
+            // the error is in the overall parity bit
+            (0, true) => EccStatus::Corrected,
+            (pos, true) if pos < word.num_bits() => {
+                word.codeword ^= 1u128 << pos;
+                EccStatus::Corrected
+            }
+            // even number of errors, or syndrome pointing outside the codeword
+            _ => EccStatus::Detected,
+        };
+
+        (word.extract_data(), status)
+    }
+
+    /// Applies a stuck bit to the codeword at the given index (`0..num_bits`).
+    pub fn inject(&mut self, stuck: &Stuck, index: usize) {
+        let mask = 1u128 << index;
+        match stuck {
+            Stuck::Zero => self.codeword &= !mask,
+            Stuck::One => self.codeword |= mask,
+            Stuck::Transient => self.codeword ^= mask,
+        };
+    }
+
+    /// Stores `value`, injects `num_bits` faults of type `stuck` in random distinct bits of the
+    /// codeword and reads it back.
+    ///
+    /// # Returns
+    ///
+    /// The value read from the faulty word and the [EccOutcome] of the fault.
+    pub fn inject_random<T: ToBits<u64>>(value: &T, stuck: &Stuck, num_bits: usize) -> (T, EccOutcome) {
+        let (read, outcome) = Self::inject_random_word(value.get_bits(), 64, stuck, num_bits);
+        (T::from_bits(read), outcome)
+    }
+
+    /// Stores the lowest `data_bits` bits of a word, injects `num_bits` faults of type `stuck` in random
+    /// distinct bits of the codeword and reads it back, as [EccWord::inject_random].
+    pub fn inject_random_word(data: u64, data_bits: usize, stuck: &Stuck, num_bits: usize) -> (u64, EccOutcome) {
+        let mut word = Self::encode_word(data, data_bits);
+        let original = word.extract_data();
+        let clean = word.clone();
+
+        let num_bits = num_bits.min(word.num_bits());
+        for index in sample(&mut rand::thread_rng(), word.num_bits(), num_bits).iter() {
+            word.inject(stuck, index);
+        }
+
+        let (read, status) = word.decode_word();
+        let outcome = if word == clean {
+            EccOutcome::NoError
+        } else if status == EccStatus::Detected {
+            EccOutcome::Detected
+        } else if read == original {
+            EccOutcome::Corrected
+        } else {
+            EccOutcome::Uncorrected
+        };
+
+        (read, outcome)
+    }
+
+    /// XOR of the positions of all the bits set to one (the overall parity bit is excluded).
+    fn syndrome(&self) -> usize {
+        (1..self.num_bits())
+            .filter(|pos| (self.codeword >> pos) & 1 == 1)
+            .fold(0, |acc, pos| acc ^ pos)
+    }
+
+    fn extract_data(&self) -> u64 {
+        let mut data: u64 = 0;
+        let mut data_idx = 0;
+        for pos in 1..self.num_bits() {
+            if !pos.is_power_of_two() {
+                if (self.codeword >> pos) & 1 == 1 {
+                    data |= 1u64 << data_idx;
+                }
+                data_idx += 1;
+            }
+        }
+        data
+    }
+}
+
+impl EccStats {
+    /// Updates the counters with the outcome of a fault.
+    pub fn record(&mut self, outcome: EccOutcome) {
+        match outcome {
+            EccOutcome::NoError => self.no_error += 1,
+            EccOutcome::Corrected => self.corrected += 1,
+            EccOutcome::Detected => self.detected += 1,
+            EccOutcome::Uncorrected => self.uncorrected += 1,
+        }
+    }
+}
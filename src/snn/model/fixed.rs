@@ -0,0 +1,482 @@
+//! The fixed point Leaky Integrate and Fire module.
+//!
+//! Neuromorphic hardware usually does not compute in `f64`, but with Q-format fixed point words of
+//! few bits. The `FixedLif` model implements the same dynamics of
+//! [LeakyIntegrateFire](super::lif::LeakyIntegrateFire), but every value of the neuron (parameters,
+//! membrane potential and the intermediate results of the datapath) is a two's complement word
+//! described by a `QFormat`, so the bit errors are injected in words of the real hardware width.
+//!
+//! A `QFormat` has one sign bit, `int_bits` integer bits and `frac_bits` fractional bits (at most 32 bits
+//! overall). The words are stored in the smallest of `i8`, `i16` and `i32` able to hold them.
+//!
+//! The full adder and the multiplier of the fixed point neuron are not modelled as trees of adders (see
+//! [heap](super::heap)): the weighted inputs are summed exactly, and a fault hits a bit of the output word of
+//! the adder or of the multiplier. So the partial sums of the full adder are never faulty, and the link of
+//! a fault of these components is ignored.
+//!
+//! Example:
+//! ```
+//! use spiking_nn_resilience::snn::model::fixed::{FixedLif, FixedNeuron, Overflow, QFormat};
+//! use spiking_nn_resilience::lif::Configuration;
+//! use spiking_nn_resilience::Model;
+//!
+//! // Q3.4: 1 sign bit, 3 integer bits and 4 fractional bits
+//! let format = QFormat::new(3, 4, Overflow::Saturating).unwrap();
+//! let mut neuron = FixedNeuron::from_conf(&Configuration::new(2.0, 0.5, 2.1, 1.0), format);
+//!
+//! let output = FixedLif::handle_spike(&mut neuron, 1.0, 1);
+//! assert_eq!(output, 1.0);
+//! ```
+
+use rand::Rng;
+
+use super::{apply_stuck, lif::{ComparatorInjection, Configuration, InjectionStruct, LeakyIntegrateFire, ResetInjection}, precision::{Precision, PrecisionHeap, WordLayout}, Model, Stuck};
+use crate::snn::fault::{Fault, FaultComponent};
+use crate::snn::mitigation::RangeCheck;
+use crate::NN;
+
+/// Behaviour of the fixed point words when a result does not fit in them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// The result is clamped to the largest (or smallest) representable value.
+    Saturating,
+    /// The bits exceeding the width of the word are discarded (two's complement wrap around).
+    Wrapping,
+}
+
+/// Q-format of a fixed point word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QFormat {
+    pub int_bits: u32,
+    pub frac_bits: u32,
+    pub overflow: Overflow,
+}
+
+/// Struct representing a fixed point Leaky Integrate and Fire neuron. All the values are raw words of `format`.
+#[derive(Clone, Debug)]
+pub struct FixedNeuron {
+    /// Format of all the words of the neuron
+    pub format: QFormat,
+    /// Rest potential
+    pub v_rest: i32,
+    /// Reset potential
+    pub v_reset: i32,
+    /// Threshold potential
+    pub v_th: i32,
+    /// Membrane's time constant
+    pub tau: i32,
+    /// Membrane's tension
+    pub v_mem: i32,
+    /// time variable representing the last time the neuron was updated
+    pub ts_old: u128,
+
+    /// injection in the word of the membrane potential
+    pub injection_vmem: Option<InjectionStruct>,
+    /// injection in one of the operands of the comparator
+    pub comparator: Option<ComparatorInjection>,
+    /// fault of the reset logic
+    pub reset: Option<ResetInjection>,
+    /// injection in the output word of the adder of the weighted inputs
+    pub adder: Option<InjectionStruct>,
+    /// injection in the output word of the multiplier computing the leak
+    pub multiplier: Option<InjectionStruct>,
+    /// injection in the output word of the exponential unit
+    pub exp_unit: Option<InjectionStruct>,
+    /// range checker used to repair the membrane potential and the threshold when they are not plausible
+    pub range_check: Option<RangeCheck>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FixedLif;
+
+impl QFormat {
+    /// Creates a new Q-format. The overall width (sign bit included) must be between 2 and 32 bits.
+    pub fn new(int_bits: u32, frac_bits: u32, overflow: Overflow) -> Result<QFormat, String> {
+        let width = 1 + int_bits + frac_bits;
+        if !(2..=32).contains(&width) {
+            return Err(format!("Invalid fixed point width: {} bits", width));
+        }
+        Ok(QFormat {
+            int_bits,
+            frac_bits,
+            overflow,
+        })
+    }
+
+    /// Number of bits of a word, sign bit included.
+    pub fn width(&self) -> u32 {
+        1 + self.int_bits + self.frac_bits
+    }
+
+    /// Layout of a word: the integer bits take the place of the exponent.
+    pub fn layout(&self) -> WordLayout {
+        WordLayout {
+            num_bits: self.width() as u64,
+            exponent_bits: self.int_bits as u64,
+        }
+    }
+
+    /// Largest representable raw word.
+    pub fn max_raw(&self) -> i32 {
+        ((1i64 << (self.width() - 1)) - 1) as i32
+    }
+
+    /// Smallest representable raw word.
+    pub fn min_raw(&self) -> i32 {
+        (-(1i64 << (self.width() - 1))) as i32
+    }
+
+    /// Brings an intermediate result back to the width of the word, according to the overflow behaviour.
+    pub fn normalize(&self, val: i64) -> i32 {
+        match self.overflow {
+            Overflow::Saturating => val.clamp(self.min_raw() as i64, self.max_raw() as i64) as i32,
+            Overflow::Wrapping => {
+                let shift = 64 - self.width();
+                ((val << shift) >> shift) as i32
+            }
+        }
+    }
+
+    /// Converts a real value to the nearest raw word. NaN is converted to zero.
+    pub fn quantize(&self, val: f64) -> i32 {
+        self.normalize((val * (1u64 << self.frac_bits) as f64).round() as i64)
+    }
+
+    /// Converts a raw word to its real value.
+    pub fn to_f64(&self, raw: i32) -> f64 {
+        raw as f64 / (1u64 << self.frac_bits) as f64
+    }
+
+    /// The bits of a raw word, in the lowest `width` bits of the word.
+    pub fn to_word(&self, raw: i32) -> u64 {
+        raw as u32 as u64 & ((1u64 << self.width()) - 1)
+    }
+
+    /// The raw word of the lowest `width` bits of a word, the inverse of [QFormat::to_word].
+    pub fn from_word(&self, word: u64) -> i32 {
+        let shift = 32 - self.width();
+        ((word as u32 as i32) << shift) >> shift
+    }
+
+    /// Applies a stuck bit at the given index (`0..width`) of a raw word.
+    pub fn stuck_word(&self, raw: i32, stuck: &Stuck, index: usize) -> i32 {
+        let index = index as u64;
+        let res = match self.width() {
+            0..=8 => apply_stuck(raw as i8, stuck, index) as i32,
+            9..=16 => apply_stuck(raw as i16, stuck, index) as i32,
+            _ => apply_stuck(raw, stuck, index),
+        };
+        // the bits over the width of the word are the extension of its sign bit
+        let shift = 32 - self.width();
+        (res << shift) >> shift
+    }
+
+    /// Applies a stuck bit at a random index of a raw word.
+    pub fn stuck_random_bit(&self, raw: i32, stuck: &Stuck) -> i32 {
+        let random_bit_index = rand::thread_rng().gen_range(0..self.width() as usize);
+        self.stuck_word(raw, stuck, random_bit_index)
+    }
+
+    /// Returns the nearest real value representable in this format.
+    pub fn round(&self, val: f64) -> f64 {
+        self.to_f64(self.quantize(val))
+    }
+}
+
+impl FixedNeuron {
+    pub fn new(v_rest: f64, v_reset: f64, v_th: f64, tau: f64, format: QFormat) -> FixedNeuron {
+        FixedNeuron {
+            format,
+            v_rest: format.quantize(v_rest),
+            v_reset: format.quantize(v_reset),
+            v_th: format.quantize(v_th),
+            tau: format.quantize(tau),
+            v_mem: 0,
+            ts_old: 0,
+
+            injection_vmem: None,
+            comparator: None,
+            reset: None,
+            adder: None,
+            multiplier: None,
+            exp_unit: None,
+            range_check: None,
+        }
+    }
+
+    pub fn from_conf(nc: &Configuration, format: QFormat) -> FixedNeuron {
+        Self::new(nc.v_rest, nc.v_reset, nc.v_threshold, nc.tau, format)
+    }
+
+    /// Create a new array of n [FixedNeuron] structs, starting from a given Configuration.
+    pub fn new_vec(conf: Configuration, n: usize, format: QFormat) -> Vec<FixedNeuron> {
+        (0..n).map(|_| FixedNeuron::from_conf(&conf, format)).collect()
+    }
+
+    fn random_injection(&self, stuck: Stuck) -> InjectionStruct {
+        InjectionStruct {
+            stuck,
+            index: rand::thread_rng().gen_range(0..self.format.width() as usize),
+        }
+    }
+}
+
+impl FixedLif {
+    /// Converts a network of [LeakyIntegrateFire] neurons to the same network of fixed point neurons,
+    /// whose parameters and weights are all rounded to `format`.
+    pub fn from_lif_network(nn: &NN<LeakyIntegrateFire>, format: QFormat) -> Result<NN<FixedLif>, String> {
+        let mut res = NN::<FixedLif>::new();
+        for layer in nn.layers.iter() {
+            let neurons = layer
+                .iter_neurons()
+                .map(|n| FixedNeuron::new(n.v_rest, n.v_reset, n.v_th, n.tau, format))
+                .collect();
+            res = res.layer(neurons, layer.input_weights.clone(), layer.intra_weights.clone())?;
+        }
+        res.use_fixed_weights(format);
+        Ok(res)
+    }
+}
+
+impl Model for FixedLif {
+    type Neuron = FixedNeuron;
+
+    type Config = Configuration;
+
+    /// Same dynamics of [LeakyIntegrateFire::handle_spike], computed on fixed point words.
+    ///
+    /// The leak factor `exp(-delta_t / tau)` is rounded to `frac_bits` fractional bits, as read from a
+    /// lookup table, and every intermediate result is brought back to the width of the word.
+    fn handle_spike(neuron: &mut FixedNeuron, weighted_input_val: f64, ts: u128) -> f64 {
+        let format = neuron.format;
+
+        //apply injection if necessary
+        if let Some(injection_vmem) = &neuron.injection_vmem {
+            neuron.v_mem = format.stuck_word(neuron.v_mem, &injection_vmem.stuck, injection_vmem.index);
+        }
+
+        let delta_t: f64 = (ts - neuron.ts_old) as f64;
+        neuron.ts_old = ts;
+
+        // leak factor in Q0.frac_bits
+        let mut leak = format.normalize(
+            ((-delta_t / format.to_f64(neuron.tau)).exp() * (1u64 << format.frac_bits) as f64).round() as i64,
+        );
+        if let Some(exp_unit) = &neuron.exp_unit {
+            leak = format.stuck_word(leak, &exp_unit.stuck, exp_unit.index);
+        }
+
+        let distance = format.normalize(neuron.v_mem as i64 - neuron.v_rest as i64);
+        let mut decay = format.normalize((distance as i64 * leak as i64) >> format.frac_bits);
+        if let Some(multiplier) = &neuron.multiplier {
+            decay = format.stuck_word(decay, &multiplier.stuck, multiplier.index);
+        }
+
+        let mut input = format.quantize(weighted_input_val);
+        if let Some(adder) = &neuron.adder {
+            input = format.stuck_word(input, &adder.stuck, adder.index);
+        }
+
+        let v_mem = format.normalize(neuron.v_rest as i64 + decay as i64);
+        neuron.v_mem = format.normalize(v_mem as i64 + input as i64);
+        if let Some(reset) = neuron.reset.as_mut() {
+            neuron.v_mem = reset.after_update(neuron.v_mem, neuron.v_reset);
+        }
+
+        //apply stuck
+        if let Some(injection_vmem) = &neuron.injection_vmem {
+            neuron.v_mem = format.stuck_word(neuron.v_mem, &injection_vmem.stuck, injection_vmem.index);
+        }
+
+        //repair the values outside the plausible ranges
+        if let Some(range_check) = &neuron.range_check {
+            let v_mem = range_check.check_v_mem(format.to_f64(neuron.v_mem), format.to_f64(neuron.v_reset));
+            neuron.v_mem = format.quantize(v_mem);
+            neuron.v_th = format.quantize(range_check.check_v_th(format.to_f64(neuron.v_th)));
+        }
+
+        // the raw words are compared as two's complement integers, as in a signed hardware comparator
+        let (v_mem, v_th) = match &neuron.comparator {
+            Some(comparator) => {
+                comparator.operands(neuron.v_mem, neuron.v_th, |raw, stuck, index| format.stuck_word(raw, stuck, index))
+            }
+            None => (neuron.v_mem, neuron.v_th),
+        };
+        if v_mem > v_th {
+            neuron.v_mem = match neuron.reset.as_mut() {
+                Some(reset) => {
+                    reset.reset(neuron.v_mem, neuron.v_reset, |raw, stuck, index| format.stuck_word(raw, stuck, index))
+                }
+                None => neuron.v_reset,
+            };
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn update_v_mem(neuron: &mut FixedNeuron, val: f64) {
+        let format = neuron.format;
+        let v_mem = format.normalize(neuron.v_mem as i64 + format.quantize(val) as i64);
+        neuron.v_mem = v_mem.max(0);
+    }
+
+    fn update_v_rest(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.v_rest = neuron.format.stuck_random_bit(neuron.v_rest, &stuck);
+    }
+
+    fn update_v_reset(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.v_reset = neuron.format.stuck_random_bit(neuron.v_reset, &stuck);
+    }
+
+    fn update_v_th(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.v_th = neuron.format.stuck_random_bit(neuron.v_th, &stuck);
+    }
+
+    fn update_tau(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.tau = neuron.format.stuck_random_bit(neuron.tau, &stuck);
+    }
+
+    /// The weighted inputs are summed by a fixed point adder: the fault hits its output word, not the
+    /// partial sums.
+    fn use_heap(neuron: &mut Self::Neuron, stuck: Stuck, _inputs: Vec<f64>) {
+        neuron.adder = Some(neuron.random_injection(stuck));
+    }
+
+    fn use_v_mem_with_injection(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.injection_vmem = Some(neuron.random_injection(stuck));
+    }
+
+    /// The fixed point adder is not a heap: its fault is applied directly on its output word.
+    fn get_heap(_neuron: &Self::Neuron) -> Option<PrecisionHeap> {
+        None
+    }
+
+    fn get_injection_vmem(neuron: &Self::Neuron) -> Option<InjectionStruct> {
+        neuron.injection_vmem.clone()
+    }
+
+    fn use_comparator(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.comparator = Some(ComparatorInjection::random(stuck, neuron.format.width() as u64));
+    }
+
+    fn get_comparator(neuron: &Self::Neuron) -> Option<ComparatorInjection> {
+        neuron.comparator.clone()
+    }
+
+    fn use_reset(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.reset = Some(ResetInjection::random(stuck, neuron.format.width() as u64));
+    }
+
+    fn use_multiplier(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.multiplier = Some(neuron.random_injection(stuck));
+    }
+
+    /// The fixed point multiplier is not a heap: its fault is applied directly on its output word.
+    fn get_multiplier(_neuron: &Self::Neuron) -> Option<PrecisionHeap> {
+        None
+    }
+
+    fn use_exp_unit(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.exp_unit = Some(neuron.random_injection(stuck));
+    }
+
+    fn get_exp_unit(neuron: &Self::Neuron) -> Option<InjectionStruct> {
+        neuron.exp_unit.clone()
+    }
+
+    fn get_parameter(neuron: &Self::Neuron, parameter: &str) -> Option<f64> {
+        let raw = match parameter {
+            "v_th" => neuron.v_th,
+            "v_rest" => neuron.v_rest,
+            "v_reset" => neuron.v_reset,
+            "v_tau" | "tau" => neuron.tau,
+            _ => return None,
+        };
+        Some(neuron.format.to_f64(raw))
+    }
+
+    fn get_parameter_word(neuron: &Self::Neuron, parameter: &str) -> Option<u64> {
+        let val = Self::get_parameter(neuron, parameter)?;
+        Some(neuron.format.to_word(neuron.format.quantize(val)))
+    }
+
+    fn set_parameter_word(neuron: &mut Self::Neuron, parameter: &str, word: u64) {
+        let val = neuron.format.to_f64(neuron.format.from_word(word));
+        Self::set_parameter(neuron, parameter, val);
+    }
+
+    fn set_parameter(neuron: &mut Self::Neuron, parameter: &str, val: f64) {
+        let raw = neuron.format.quantize(val);
+        match parameter {
+            "v_th" => neuron.v_th = raw,
+            "v_rest" => neuron.v_rest = raw,
+            "v_reset" => neuron.v_reset = raw,
+            "v_tau" | "tau" => neuron.tau = raw,
+            _ => {}
+        }
+    }
+
+    fn state(neuron: &Self::Neuron) -> Vec<(&'static str, f64)> {
+        vec![
+            ("v_mem", neuron.format.to_f64(neuron.v_mem)),
+            ("ts_old", neuron.ts_old as f64),
+            ("v_th", neuron.format.to_f64(neuron.v_th)),
+            // the word stored in the register of the membrane potential
+            ("v_mem_raw", neuron.v_mem as f64),
+        ]
+    }
+
+    fn use_range_check(neuron: &mut Self::Neuron, range_check: Option<RangeCheck>) {
+        neuron.range_check = range_check;
+    }
+
+    /// The precision of a fixed point neuron is given by its `QFormat`: floating point precisions are ignored.
+    fn use_precision(_neuron: &mut Self::Neuron, _precision: Precision) {}
+
+    fn word_layout(neuron: &Self::Neuron) -> WordLayout {
+        neuron.format.layout()
+    }
+
+    /// The faults of the full adder and of the multiplier hit their output word: the link is ignored.
+    fn use_fault(neuron: &mut Self::Neuron, fault: &Fault, _num_inputs: usize) -> Result<(), String> {
+        let format = neuron.format;
+        if fault.bit as u32 >= format.width() {
+            return Err(format!("Bit {} out of range of a {} bits word", fault.bit, format.width()));
+        }
+        let stuck = fault.stuck.clone();
+        let index = fault.bit;
+        match fault.component {
+            FaultComponent::VTh => neuron.v_th = format.stuck_word(neuron.v_th, &stuck, index),
+            FaultComponent::VRest => neuron.v_rest = format.stuck_word(neuron.v_rest, &stuck, index),
+            FaultComponent::VReset => neuron.v_reset = format.stuck_word(neuron.v_reset, &stuck, index),
+            FaultComponent::Tau => neuron.tau = format.stuck_word(neuron.tau, &stuck, index),
+            FaultComponent::VMem => neuron.injection_vmem = Some(InjectionStruct { stuck, index }),
+            FaultComponent::FullAdder => neuron.adder = Some(InjectionStruct { stuck, index }),
+            FaultComponent::Comparator(operand) => {
+                neuron.comparator = Some(ComparatorInjection::new(stuck, operand, index))
+            }
+            FaultComponent::Reset(reset_fault) => neuron.reset = Some(ResetInjection::new(reset_fault, stuck, index)),
+            FaultComponent::Multiplier => neuron.multiplier = Some(InjectionStruct { stuck, index }),
+            FaultComponent::Exponential => neuron.exp_unit = Some(InjectionStruct { stuck, index }),
+            FaultComponent::Weight => return Err("A weight is not a component of the neuron".to_string()),
+        }
+        Ok(())
+    }
+
+    fn remove_fault(neuron: &mut Self::Neuron, fault: &Fault, fault_free: &Self::Neuron) {
+        match fault.component {
+            FaultComponent::VTh => neuron.v_th = fault_free.v_th,
+            FaultComponent::VRest => neuron.v_rest = fault_free.v_rest,
+            FaultComponent::VReset => neuron.v_reset = fault_free.v_reset,
+            FaultComponent::Tau => neuron.tau = fault_free.tau,
+            FaultComponent::VMem => neuron.injection_vmem = fault_free.injection_vmem.clone(),
+            FaultComponent::FullAdder => neuron.adder = fault_free.adder.clone(),
+            FaultComponent::Comparator(_) => neuron.comparator = fault_free.comparator.clone(),
+            FaultComponent::Reset(_) => neuron.reset = fault_free.reset.clone(),
+            FaultComponent::Multiplier => neuron.multiplier = fault_free.multiplier.clone(),
+            FaultComponent::Exponential => neuron.exp_unit = fault_free.exp_unit.clone(),
+            FaultComponent::Weight => {}
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! # Heap Calculator
 //!
-//! This module defines a heap calculator for performing sum operations on a binary heap.
+//! This module defines a heap calculator for performing sum and product operations on a binary heap.
 //! The heap calculator employs the concept of links, where each link contains a value, a stuck bit
 //! for fault injection, and a mask for manipulating individual bits.
 //!
@@ -23,6 +23,10 @@
 //! let result = heap_calculator.sum_all(&inputs);
 //!
 //! println!("Result: {}", result);
+//!
+//! // The same structure models a faulty multiplier.
+//! let mut multiplier: HeapCalculator<f64, u64> = HeapCalculator::multiplier(Stuck::One);
+//! let product = multiplier.multiply_all(&[3.0, 0.5]);
 //! ```
 //!
 //! ## Stuck Types
@@ -93,62 +97,39 @@ where
         }
     }
 
-    pub fn sum(&self, link: &Link<T, U>) -> T {
-        if let Some(stuck) = &self.stuck_bit {
-            let mut bits: U = self.value.get_bits(); //get the bit representation of the value
-
-            match stuck {
-                Stuck::Zero => bits &= !(self.mask.as_ref().unwrap().clone()),
-                Stuck::One => bits |= self.mask.as_ref().unwrap().clone(),
-                Stuck::Transient => bits = self.invert_bit_at(&bits, self.mask.clone()),
-            };
-            let new_val = T::from_bits(bits);
-            return new_val + link.value.clone();
-        }
-
-        if let Some(stuck) = &link.stuck_bit {
-            let mut bits: U = link.value.get_bits(); //get the bit representation of the value
-            match stuck {
-                Stuck::Zero => bits &= !(link.mask.as_ref().unwrap().clone()),
-                Stuck::One => bits |= link.mask.as_ref().unwrap().clone(),
-                Stuck::Transient => bits = self.invert_bit_at(&bits, link.mask.clone()),
-            };
-            let new_val = T::from_bits(bits);
-            return new_val + self.value.clone();
+    /// Creates a new link with fault injection at the given bit.
+    pub fn with_bit(value: T, stuck_bit: Option<Stuck>, index: u64) -> Self {
+        Link {
+            stuck_bit,
+            marker: PhantomData,
+            mask: Some(value.create_mask(index)),
+            value,
         }
-
-        self.value.clone() + link.value.clone()
     }
 
-    /* pub fn product(&self, link: &Link<T, U>) -> T {
-        if let Some(stuck) = &self.stuck_bit {
-            let mut bits: U = self.value.get_bits(); //get the bit representation of the value
-
-            match stuck {
-                Stuck::Zero => bits &= !(self.mask.as_ref().unwrap().clone()),
-                Stuck::One => bits |= self.mask.as_ref().unwrap().clone(),
-                Stuck::Transient => bits = self.invert_bit_at(&bits, link),
-            };
-
-            let new_val = T::from_bits(bits);
-
-            return new_val * link.value.clone();
+    /// Reads the value of the link, through its stuck bit if any.
+    pub fn read(&self) -> T {
+        match &self.stuck_bit {
+            Some(stuck) => {
+                let mut bits: U = self.value.get_bits(); //get the bit representation of the value
+                match stuck {
+                    Stuck::Zero => bits &= !(self.mask.as_ref().unwrap().clone()),
+                    Stuck::One => bits |= self.mask.as_ref().unwrap().clone(),
+                    Stuck::Transient => bits = self.invert_bit_at(&bits, self.mask.clone()),
+                };
+                T::from_bits(bits)
+            }
+            None => self.value.clone(),
         }
+    }
 
-        if let Some(stuck) = &link.stuck_bit {
-            let mut bits: U = link.value.get_bits(); //get the bit representation of the value
-
-            match stuck {
-                Stuck::Zero => bits &= !(self.mask.as_ref().unwrap().clone()),
-                Stuck::One => bits |= self.mask.as_ref().unwrap().clone(),
-                Stuck::Transient => bits = self.invert_bit_at(&bits, link),
-            };
-            let new_val = T::from_bits(bits);
-            return new_val * self.value.clone();
-        }
+    pub fn sum(&self, link: &Link<T, U>) -> T {
+        self.read() + link.read()
+    }
 
-        self.value.clone() * link.value.clone()
-    } */
+    pub fn product(&self, link: &Link<T, U>) -> T {
+        self.read() * link.read()
+    }
 
     pub fn invert_bit_at(&self, bits: &U, mask: Option<U>) -> U {
         if (bits.clone() & mask.as_ref().unwrap().clone()) != U::default() {
@@ -187,7 +168,7 @@ where
         ];
         // println!("{}   {}", heap_vec.len(), dim);
         //choose the link to apply the injection
-        let index = rand::thread_rng().gen_range(0..heap_length);
+        let index = rand::thread_rng().gen_range(0..adder_links(dim));
         heap_vec[index] = Link::new(T::default(), Some(stuck));
 
         // println!("stuck link: {}", index);
@@ -195,6 +176,62 @@ where
         HeapCalculator { heap_vec }
     }
 
+    /// Creates a new `HeapCalculator` with fault injection at a given bit of a given link.
+    ///
+    /// # Arguments
+    ///
+    /// * `dim` - The number of elements in the input.
+    /// * `stuck` - The type of fault to inject.
+    /// * `link` - The index of the faulty link, lower than [adder_links]. Random if [None].
+    /// * `bit` - The index of the faulty bit of the link.
+    pub fn with_fault(dim: usize, stuck: Stuck, link: Option<usize>, bit: u64) -> Result<Self, String> {
+        Self::faulty(dim, adder_links(dim), stuck, link, Some(bit))
+    }
+
+    /// Creates a new multiplier of two operands with fault injection at a random position.
+    pub fn multiplier(stuck: Stuck) -> Self {
+        Self::faulty(2, num_links(2), stuck, None, None).unwrap()
+    }
+
+    /// Creates a new multiplier of two operands with fault injection at a given bit of a given link.
+    ///
+    /// # Arguments
+    ///
+    /// * `stuck` - The type of fault to inject.
+    /// * `link` - The index of the faulty link, lower than [num_links] of 2 inputs: an operand or the
+    ///   product. Random if [None].
+    /// * `bit` - The index of the faulty bit of the link.
+    pub fn multiplier_with_fault(stuck: Stuck, link: Option<usize>, bit: u64) -> Result<Self, String> {
+        Self::faulty(2, num_links(2), stuck, link, Some(bit))
+    }
+
+    /// A heap of `dim` inputs with the fault at a bit of one of its first `links` links, both random if
+    /// [None].
+    fn faulty(dim: usize, links: usize, stuck: Stuck, link: Option<usize>, bit: Option<u64>) -> Result<Self, String> {
+        let heap_length = 2 * dim;
+        let link = link.unwrap_or_else(|| rand::thread_rng().gen_range(0..links));
+        if link >= links {
+            return Err(format!("Link {} out of range of a heap of {} links", link, links));
+        }
+        if let Some(bit) = bit.filter(|bit| *bit >= T::default().num_bits()) {
+            return Err(format!("Bit {} out of range of a {} bits word", bit, T::default().num_bits()));
+        }
+        let mut heap_vec: Vec<Link<T, U>> = vec![
+            Link {
+                value: T::default(),
+                stuck_bit: None,
+                mask: None,
+                marker: PhantomData,
+            };
+            heap_length
+        ];
+        heap_vec[link] = match bit {
+            Some(bit) => Link::with_bit(T::default(), Some(stuck), bit),
+            None => Link::new(T::default(), Some(stuck)),
+        };
+        Ok(HeapCalculator { heap_vec })
+    }
+
     pub fn sum_all(&mut self, inputs: &[T]) -> T {
         let len = self.heap_vec.len();
         // println!("len: {}", len);
@@ -228,30 +265,46 @@ where
         self.heap_vec[len - 2].value.clone()
     }
 
-    /*  pub fn multiply_all(&mut self, inputs: &[T]) -> T {
+    /// Multiplies all the input values through the heap, applying the fault of the faulty link.
+    ///
+    /// The number of inputs should be equal to the dimension of the heap: the unused leaves hold
+    /// zero, so they would cancel the product.
+    pub fn multiply_all(&mut self, inputs: &[T]) -> T {
         let len = self.heap_vec.len();
-        println!("len: {}", len);
-        for (i, val) in inputs.into_iter().enumerate() {
+        for (i, val) in inputs.iter().enumerate() {
             self.heap_vec[i].value = val.clone();
         }
         let mut start: usize = 0;
 
         for lv in 0..log2(len) {
-            println!("lv: {}", lv);
-            for i in (0..len >> (lv + 1)).step_by(2) {
-                println!(
-                    "{} {}",
-                    self.heap_vec[start + i].value,
-                    self.heap_vec[start + i + 1].value
-                );
+            let lv_dim = len >> (lv + 1);
+
+            for i in (0..lv_dim).step_by(2) {
                 let tmp = self.heap_vec[start + i].product(&self.heap_vec[start + i + 1]);
-                self.heap_vec[start + (len >> (lv + 1)) + i / 2].value = tmp;
+                self.heap_vec[start + lv_dim + i / 2].value = tmp;
             }
-            start += (len >> (lv + 1));
+            start += lv_dim;
         }
-        self.heap_vec[len - 2].value.clone()
-    } */
+        self.heap_vec[len - 2].read()
+    }
+}
+/// The number of inputs of the heap of a full adder summing `num_inputs` values: the next power of two.
+pub fn adder_dim(num_inputs: usize) -> usize {
+    (2u32).pow(((num_inputs as f64).log2().ceil()) as u32) as usize
+}
+
+/// The number of links of a heap of `dim` inputs that hold a value: the `dim` inputs, then the partial
+/// results up to the output, the last one.
+pub fn num_links(dim: usize) -> usize {
+    (2 * dim).saturating_sub(1).max(1)
+}
+
+/// The number of links of the heap of a full adder of `dim` inputs where a fault is injected: all the
+/// `2 * dim` links, so a fault in the output link or in the last one, which holds no value, is masked.
+pub fn adder_links(dim: usize) -> usize {
+    2 * dim
 }
+
 fn log2(len: usize) -> usize {
     let mut shifts = 0;
     let mut n = len;
@@ -36,15 +36,57 @@
 
 use rand::Rng;
 use std::f64;
+use std::cmp::Ordering;
 
-use super::{heap::HeapCalculator, Model, Stuck};
+use super::heap::adder_dim;
+use super::{precision::{Precision, PrecisionHeap, WordLayout}, Model, Stuck};
+use crate::snn::fault::{Fault, FaultComponent};
+use crate::snn::mitigation::RangeCheck;
 
 /// Struct representing an injection of a specific stuck value at a given index in the membrane potential.
-/// Also used to inject the error in the comparator
 #[derive(Clone, Debug)]
 pub struct InjectionStruct {
-    stuck: Stuck,
-    index: usize,
+    pub(crate) stuck: Stuck,
+    pub(crate) index: usize,
+}
+
+/// Operand of the comparator deciding whether the neuron fires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComparatorOperand {
+    VMem,
+    VTh,
+}
+
+/// Failure mode of the logic resetting the membrane potential after a spike.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetFault {
+    /// The membrane potential keeps its value after the spike
+    NotApplied,
+    /// The reset stays asserted, and it is applied again after the next update of the membrane potential
+    AppliedTwice,
+    /// The membrane potential is reset to a value with a stuck bit
+    Corrupted,
+}
+
+/// Struct representing a fault in the reset logic of a neuron.
+#[derive(Clone, Debug)]
+pub struct ResetInjection {
+    pub(crate) fault: ResetFault,
+    pub(crate) stuck: Stuck,
+    pub(crate) index: usize,
+    /// true if the reset is still asserted from the last spike
+    pub(crate) pending: bool,
+}
+
+/// Struct representing a stuck bit at a given index of one of the operands of the comparator.
+/// The fault only changes the value seen by the comparator, not the one stored in the neuron.
+/// The faulty words are compared as in a hardware comparator: by sign and magnitude in floating point
+/// (see [f64::total_cmp]), as two's complement integers in fixed point.
+#[derive(Clone, Debug)]
+pub struct ComparatorInjection {
+    pub(crate) stuck: Stuck,
+    pub(crate) operand: ComparatorOperand,
+    pub(crate) index: usize,
 }
 
 /// Struct representing a Leaky Integrate and Fire (LIF) neuron.
@@ -65,20 +107,30 @@ pub struct LifNeuron {
     pub ts_old: u128,
 
     /// Heap vector used to compute the sum when there is a bit error injection in a full adder
-    pub heap_tree: Option<HeapCalculator<f64, u64>>,
+    pub heap_tree: Option<PrecisionHeap>,
     /// struct to store information used to update the v_mem of the neuron when there is an error injection
     pub injection_vmem: Option<InjectionStruct>,
-    /// struct to store information used to update the v_mem of the neuron when there is an error injection in the comparator
-    pub comparator: Option<InjectionStruct>,
+    /// struct to store the faulty bit of the operands of the comparator
+    pub comparator: Option<ComparatorInjection>,
+    /// struct to store the fault of the reset logic
+    pub reset: Option<ResetInjection>,
+    /// Heap vector used to compute the product of the leak when there is a bit error injection in the multiplier
+    pub multiplier: Option<PrecisionHeap>,
+    /// struct to store information used to corrupt the result of the exponential unit
+    pub exp_unit: Option<InjectionStruct>,
+    /// numeric precision of all the values and operations of the neuron
+    pub precision: Precision,
+    /// range checker used to repair the membrane potential and the threshold when they are not plausible
+    pub range_check: Option<RangeCheck>,
 }
 
 /// Struct representing a specific configuration for LIF neurons.
 #[derive(Clone, Debug)]
 pub struct Configuration {
-    v_rest: f64,
-    v_reset: f64,
-    v_threshold: f64,
-    tau: f64,
+    pub(crate) v_rest: f64,
+    pub(crate) v_reset: f64,
+    pub(crate) v_threshold: f64,
+    pub(crate) tau: f64,
 }
 
 // IMPLEMENTATION FOR LIF NEURONS & LIF NEURON CONFIG
@@ -97,6 +149,11 @@ impl LifNeuron {
             heap_tree: None,
             injection_vmem: None,
             comparator: None,
+            reset: None,
+            multiplier: None,
+            exp_unit: None,
+            precision: Precision::F64,
+            range_check: None,
         }
     }
 
@@ -120,6 +177,88 @@ impl LifNeuron {
         res
     }
 }
+impl ComparatorInjection {
+    pub fn new(stuck: Stuck, operand: ComparatorOperand, index: usize) -> ComparatorInjection {
+        ComparatorInjection { stuck, operand, index }
+    }
+
+    /// Creates an injection in a random bit of a random operand, with words of `num_bits` bits.
+    pub fn random(stuck: Stuck, num_bits: u64) -> ComparatorInjection {
+        let mut rng = rand::thread_rng();
+        let operand = if rng.gen_bool(0.5) {
+            ComparatorOperand::VMem
+        } else {
+            ComparatorOperand::VTh
+        };
+        ComparatorInjection::new(stuck, operand, rng.gen_range(0..num_bits as usize))
+    }
+
+    /// Returns the operands `(v_mem, v_th)` as seen by the comparator.
+    ///
+    /// # Arguments
+    ///
+    /// * `v_mem` - The membrane potential stored in the neuron.
+    /// * `v_th` - The threshold stored in the neuron.
+    /// * `stuck_bit` - The function applying a stuck bit to a word of the neuron.
+    pub fn operands<T>(&self, v_mem: T, v_th: T, stuck_bit: impl Fn(T, &Stuck, usize) -> T) -> (T, T) {
+        match self.operand {
+            ComparatorOperand::VMem => (stuck_bit(v_mem, &self.stuck, self.index), v_th),
+            ComparatorOperand::VTh => (v_mem, stuck_bit(v_th, &self.stuck, self.index)),
+        }
+    }
+}
+
+impl ResetInjection {
+    /// Creates a fault in the reset logic. `stuck` and `index` are only used by [ResetFault::Corrupted].
+    pub fn new(fault: ResetFault, stuck: Stuck, index: usize) -> ResetInjection {
+        ResetInjection {
+            fault,
+            stuck,
+            index,
+            pending: false,
+        }
+    }
+
+    /// Creates a fault with a random failure mode, corrupting a random bit of words of `num_bits` bits.
+    pub fn random(stuck: Stuck, num_bits: u64) -> ResetInjection {
+        let mut rng = rand::thread_rng();
+        let fault = match rng.gen_range(0..3) {
+            0 => ResetFault::NotApplied,
+            1 => ResetFault::AppliedTwice,
+            _ => ResetFault::Corrupted,
+        };
+        ResetInjection::new(fault, stuck, rng.gen_range(0..num_bits as usize))
+    }
+
+    /// Returns the membrane potential after a spike.
+    ///
+    /// # Arguments
+    ///
+    /// * `v_mem` - The membrane potential that generated the spike.
+    /// * `v_reset` - The reset potential of the neuron.
+    /// * `stuck_bit` - The function applying a stuck bit to a word of the neuron.
+    pub fn reset<T>(&mut self, v_mem: T, v_reset: T, stuck_bit: impl Fn(T, &Stuck, usize) -> T) -> T {
+        match self.fault {
+            ResetFault::NotApplied => v_mem,
+            ResetFault::AppliedTwice => {
+                self.pending = true;
+                v_reset
+            }
+            ResetFault::Corrupted => stuck_bit(v_reset, &self.stuck, self.index),
+        }
+    }
+
+    /// Returns the membrane potential after an update, resetting it again if the reset is still asserted.
+    pub fn after_update<T>(&mut self, v_mem: T, v_reset: T) -> T {
+        if self.pending {
+            self.pending = false;
+            v_reset
+        } else {
+            v_mem
+        }
+    }
+}
+
 // implementation of the model trait
 
 impl Configuration {
@@ -158,17 +297,12 @@ impl Model for LeakyIntegrateFire {
     ///
 
     fn handle_spike(neuron: &mut LifNeuron, weighted_input_val: f64, ts: u128) -> f64 {
+        let precision = neuron.precision;
         //apply injection if necessary
         let mut random_bit_index: usize = 0;
         if let Some(injection_vmem) = Self::get_injection_vmem(neuron) {
             random_bit_index = injection_vmem.index;
-            let mut bits: u64 = neuron.v_mem.to_bits();
-            match injection_vmem.stuck {
-                Stuck::Zero => bits &= !(1u64 << random_bit_index),
-                Stuck::One => bits |= 1u64 << random_bit_index,
-                Stuck::Transient => bits ^= 1u64 << random_bit_index,
-            };
-            neuron.v_mem = f64::from_bits(bits);
+            neuron.v_mem = precision.stuck_bit(neuron.v_mem, &injection_vmem.stuck, random_bit_index);
         }
 
         //do the calculations
@@ -176,112 +310,90 @@ impl Model for LeakyIntegrateFire {
         let delta_t: f64 = (ts - neuron.ts_old) as f64;
         neuron.ts_old = ts;
 
-        // compute the new v_mem value
-        neuron.v_mem = neuron.v_rest
-            + (neuron.v_mem - neuron.v_rest) * (-delta_t / neuron.tau).exp()
-            + weighted_input_val;
+        // compute the new v_mem value, rounding every result to the precision of the neuron
+        let mut leak = precision.round((-delta_t / neuron.tau).exp());
+        if let Some(exp_unit) = Self::get_exp_unit(neuron) {
+            leak = precision.stuck_bit(leak, &exp_unit.stuck, exp_unit.index);
+        }
+        let distance = precision.round(neuron.v_mem - neuron.v_rest);
+        let decay = if let Some(mut multiplier) = Self::get_multiplier(neuron) {
+            multiplier.multiply_all(&[distance, leak])
+        } else {
+            precision.round(distance * leak)
+        };
+        neuron.v_mem = precision.round(precision.round(neuron.v_rest + decay) + weighted_input_val);
+        if let Some(reset) = neuron.reset.as_mut() {
+            neuron.v_mem = reset.after_update(neuron.v_mem, neuron.v_reset);
+        }
 
         //apply stuck
         if let Some(injection_vmem) = Self::get_injection_vmem(neuron) {
-            let mut bits: u64 = neuron.v_mem.to_bits();
-            match injection_vmem.stuck {
-                Stuck::Zero => bits &= !(1u64 << random_bit_index),
-                Stuck::One => bits |= 1u64 << random_bit_index,
-                Stuck::Transient => bits ^= 1u64 << random_bit_index,
-            };
-            neuron.v_mem = f64::from_bits(bits);
+            neuron.v_mem = precision.stuck_bit(neuron.v_mem, &injection_vmem.stuck, random_bit_index);
         }
 
-        //apply stuck
-        if let Some(comparator) = Self::get_comparator(neuron) {
-            match comparator.stuck {
-                Stuck::Zero => 0.0,
-                Stuck::One => 1.0,
-                Stuck::Transient => {
-                    if neuron.v_mem > neuron.v_th {
-                        neuron.v_mem = neuron.v_reset;
-                        0.0
-                    } else {
-                        1.0
-                    }
-                }
-            }
+        //repair the values outside the plausible ranges
+        if let Some(range_check) = &neuron.range_check {
+            neuron.v_mem = range_check.check_v_mem(neuron.v_mem, neuron.v_reset);
+            neuron.v_th = range_check.check_v_th(neuron.v_th);
         }
-        // no injection
-        else {
-            if neuron.v_mem > neuron.v_th {
-                neuron.v_mem = neuron.v_reset;
-                1.0
-            } else {
-                0.0
+
+        //compare the operands, one of them possibly with a stuck bit
+        let fired = match Self::get_comparator(neuron) {
+            // the words are compared by sign and magnitude, as in a hardware comparator: a NaN is above or
+            // below all the numbers with its sign, and -0 is below +0
+            Some(comparator) => {
+                let (v_mem, v_th) = comparator.operands(neuron.v_mem, neuron.v_th, |val, stuck, index| {
+                    precision.stuck_bit(val, stuck, index)
+                });
+                v_mem.total_cmp(&v_th) == Ordering::Greater
             }
+            None => neuron.v_mem > neuron.v_th,
+        };
+        if fired {
+            neuron.v_mem = match neuron.reset.as_mut() {
+                Some(reset) => reset.reset(neuron.v_mem, neuron.v_reset, |val, stuck, index| {
+                    precision.stuck_bit(val, stuck, index)
+                }),
+                None => neuron.v_reset,
+            };
+            1.0
+        } else {
+            0.0
         }
     }
 
     fn update_v_mem(neuron: &mut LifNeuron, val: f64) {
         if neuron.v_mem + val >= 0.0 {
-            neuron.v_mem += val;
+            neuron.v_mem = neuron.precision.round(neuron.v_mem + val);
         } else {
             neuron.v_mem = 0.0;
         }
     }
 
     fn update_v_rest(neuron: &mut Self::Neuron, stuck: Stuck) {
-        let mut bits = neuron.v_rest.to_bits();
-        //println!("old bit: {}",bits);
-        let random_bit_index = rand::thread_rng().gen_range(0..64);
-        match stuck {
-            Stuck::Zero => bits &= !(1u64 << random_bit_index),
-            Stuck::One => bits |= 1u64 << random_bit_index,
-            Stuck::Transient => bits ^= 1u64 << random_bit_index,
-        };
-        //println!("update_v_rest: {}",random_bit_index);
-        //println!("new bit: {}",bits);
-        neuron.v_rest = f64::from_bits(bits);
+        neuron.v_rest = neuron.precision.stuck_random_bit(neuron.v_rest, &stuck);
     }
 
     fn update_v_reset(neuron: &mut Self::Neuron, stuck: Stuck) {
-        let mut bits: u64 = neuron.v_reset.to_bits();
-        let random_bit_index = rand::thread_rng().gen_range(0..64);
-        match stuck {
-            Stuck::Zero => bits &= !(1u64 << random_bit_index),
-            Stuck::One => bits |= 1u64 << random_bit_index,
-            Stuck::Transient => bits ^= 1u64 << random_bit_index,
-        };
-        neuron.v_reset = f64::from_bits(bits);
+        neuron.v_reset = neuron.precision.stuck_random_bit(neuron.v_reset, &stuck);
     }
 
     fn update_v_th(neuron: &mut Self::Neuron, stuck: Stuck) {
-        let mut bits: u64 = neuron.v_th.to_bits();
-        let random_bit_index = rand::thread_rng().gen_range(0..64);
-        match stuck {
-            Stuck::Zero => bits &= !(1u64 << random_bit_index),
-            Stuck::One => bits |= 1u64 << random_bit_index,
-            Stuck::Transient => bits ^= 1u64 << random_bit_index,
-        };
-
-        neuron.v_th = f64::from_bits(bits);
+        neuron.v_th = neuron.precision.stuck_random_bit(neuron.v_th, &stuck);
     }
 
     fn update_tau(neuron: &mut Self::Neuron, stuck: Stuck) {
-        let mut bits: u64 = neuron.tau.to_bits();
-        let random_bit_index = rand::thread_rng().gen_range(0..64);
-        match stuck {
-            Stuck::Zero => bits &= !(1u64 << random_bit_index),
-            Stuck::One => bits |= 1u64 << random_bit_index,
-            Stuck::Transient => bits ^= 1u64 << random_bit_index,
-        };
-        neuron.tau = f64::from_bits(bits);
+        neuron.tau = neuron.precision.stuck_random_bit(neuron.tau, &stuck);
     }
 
     fn use_heap(neuron: &mut Self::Neuron, stuck: Stuck, inputs: Vec<f64>) {
-        let dim = (2u32).pow(((inputs.len() as f64).log2().ceil()) as u32) as usize;
-        let heap_calculator = HeapCalculator::new(dim, stuck);
+        let dim = adder_dim(inputs.len());
+        let heap_calculator = PrecisionHeap::new(neuron.precision, dim, stuck);
         neuron.heap_tree = Some(heap_calculator);
     }
 
     fn use_v_mem_with_injection(neuron: &mut Self::Neuron, stuck: Stuck) {
-        let random_bit_index = rand::thread_rng().gen_range(0..64);
+        let random_bit_index = rand::thread_rng().gen_range(0..neuron.precision.num_bits() as usize);
         let injection_vmem = InjectionStruct {
             stuck,
             index: random_bit_index,
@@ -289,7 +401,7 @@ impl Model for LeakyIntegrateFire {
         neuron.injection_vmem = Some(injection_vmem);
     }
 
-    fn get_heap(neuron: &Self::Neuron) -> Option<HeapCalculator<f64, u64>> {
+    fn get_heap(neuron: &Self::Neuron) -> Option<PrecisionHeap> {
         neuron.heap_tree.clone()
     }
     fn get_injection_vmem(neuron: &Self::Neuron) -> Option<InjectionStruct> {
@@ -297,11 +409,136 @@ impl Model for LeakyIntegrateFire {
     }
 
     fn use_comparator(neuron: &mut Self::Neuron, stuck: Stuck) {
-        let comparator = InjectionStruct { stuck, index: 0 };
+        let comparator = ComparatorInjection::random(stuck, neuron.precision.num_bits());
         neuron.comparator = Some(comparator);
     }
 
-    fn get_comparator(neuron: &Self::Neuron) -> Option<InjectionStruct> {
+    fn get_comparator(neuron: &Self::Neuron) -> Option<ComparatorInjection> {
         neuron.comparator.clone()
     }
+
+    fn use_reset(neuron: &mut Self::Neuron, stuck: Stuck) {
+        neuron.reset = Some(ResetInjection::random(stuck, neuron.precision.num_bits()));
+    }
+
+    fn use_multiplier(neuron: &mut Self::Neuron, stuck: Stuck) {
+        // two operands: the distance from the rest potential and the leak factor
+        neuron.multiplier = Some(PrecisionHeap::multiplier(neuron.precision, stuck));
+    }
+
+    fn get_multiplier(neuron: &Self::Neuron) -> Option<PrecisionHeap> {
+        neuron.multiplier.clone()
+    }
+
+    fn use_exp_unit(neuron: &mut Self::Neuron, stuck: Stuck) {
+        let random_bit_index = rand::thread_rng().gen_range(0..neuron.precision.num_bits() as usize);
+        neuron.exp_unit = Some(InjectionStruct {
+            stuck,
+            index: random_bit_index,
+        });
+    }
+
+    fn get_exp_unit(neuron: &Self::Neuron) -> Option<InjectionStruct> {
+        neuron.exp_unit.clone()
+    }
+
+    fn get_parameter(neuron: &Self::Neuron, parameter: &str) -> Option<f64> {
+        match parameter {
+            "v_th" => Some(neuron.v_th),
+            "v_rest" => Some(neuron.v_rest),
+            "v_reset" => Some(neuron.v_reset),
+            "v_tau" | "tau" => Some(neuron.tau),
+            _ => None,
+        }
+    }
+
+    fn get_parameter_word(neuron: &Self::Neuron, parameter: &str) -> Option<u64> {
+        Self::get_parameter(neuron, parameter).map(|val| neuron.precision.to_word(val))
+    }
+
+    fn set_parameter_word(neuron: &mut Self::Neuron, parameter: &str, word: u64) {
+        let val = neuron.precision.from_word(word);
+        Self::set_parameter(neuron, parameter, val);
+    }
+
+    fn use_range_check(neuron: &mut Self::Neuron, range_check: Option<RangeCheck>) {
+        neuron.range_check = range_check;
+    }
+
+    fn use_precision(neuron: &mut Self::Neuron, precision: Precision) {
+        neuron.precision = precision;
+        neuron.v_rest = precision.round(neuron.v_rest);
+        neuron.v_reset = precision.round(neuron.v_reset);
+        neuron.v_th = precision.round(neuron.v_th);
+        neuron.tau = precision.round(neuron.tau);
+        neuron.v_mem = precision.round(neuron.v_mem);
+    }
+
+    fn word_layout(neuron: &Self::Neuron) -> WordLayout {
+        neuron.precision.layout()
+    }
+
+    fn use_fault(neuron: &mut Self::Neuron, fault: &Fault, num_inputs: usize) -> Result<(), String> {
+        let precision = neuron.precision;
+        if fault.bit as u64 >= precision.num_bits() {
+            return Err(format!("Bit {} out of range of a {} bits word", fault.bit, precision.num_bits()));
+        }
+        let stuck = fault.stuck.clone();
+        let index = fault.bit;
+        match fault.component {
+            FaultComponent::VTh => neuron.v_th = precision.stuck_bit(neuron.v_th, &stuck, index),
+            FaultComponent::VRest => neuron.v_rest = precision.stuck_bit(neuron.v_rest, &stuck, index),
+            FaultComponent::VReset => neuron.v_reset = precision.stuck_bit(neuron.v_reset, &stuck, index),
+            FaultComponent::Tau => neuron.tau = precision.stuck_bit(neuron.tau, &stuck, index),
+            FaultComponent::VMem => neuron.injection_vmem = Some(InjectionStruct { stuck, index }),
+            FaultComponent::FullAdder => {
+                let dim = adder_dim(num_inputs);
+                neuron.heap_tree = Some(PrecisionHeap::with_fault(precision, dim, stuck, fault.link, index as u64)?);
+            }
+            FaultComponent::Comparator(operand) => {
+                neuron.comparator = Some(ComparatorInjection::new(stuck, operand, index))
+            }
+            FaultComponent::Reset(reset_fault) => neuron.reset = Some(ResetInjection::new(reset_fault, stuck, index)),
+            FaultComponent::Multiplier => {
+                neuron.multiplier = Some(PrecisionHeap::multiplier_with_fault(precision, stuck, fault.link, index as u64)?);
+            }
+            FaultComponent::Exponential => neuron.exp_unit = Some(InjectionStruct { stuck, index }),
+            FaultComponent::Weight => return Err("A weight is not a component of the neuron".to_string()),
+        }
+        Ok(())
+    }
+
+    fn remove_fault(neuron: &mut Self::Neuron, fault: &Fault, fault_free: &Self::Neuron) {
+        match fault.component {
+            FaultComponent::VTh => neuron.v_th = fault_free.v_th,
+            FaultComponent::VRest => neuron.v_rest = fault_free.v_rest,
+            FaultComponent::VReset => neuron.v_reset = fault_free.v_reset,
+            FaultComponent::Tau => neuron.tau = fault_free.tau,
+            FaultComponent::VMem => neuron.injection_vmem = fault_free.injection_vmem.clone(),
+            FaultComponent::FullAdder => neuron.heap_tree = fault_free.heap_tree.clone(),
+            FaultComponent::Comparator(_) => neuron.comparator = fault_free.comparator.clone(),
+            FaultComponent::Reset(_) => neuron.reset = fault_free.reset.clone(),
+            FaultComponent::Multiplier => neuron.multiplier = fault_free.multiplier.clone(),
+            FaultComponent::Exponential => neuron.exp_unit = fault_free.exp_unit.clone(),
+            FaultComponent::Weight => {}
+        }
+    }
+
+    fn set_parameter(neuron: &mut Self::Neuron, parameter: &str, val: f64) {
+        match parameter {
+            "v_th" => neuron.v_th = val,
+            "v_rest" => neuron.v_rest = val,
+            "v_reset" => neuron.v_reset = val,
+            "v_tau" | "tau" => neuron.tau = val,
+            _ => {}
+        }
+    }
+
+    fn state(neuron: &Self::Neuron) -> Vec<(&'static str, f64)> {
+        vec![
+            ("v_mem", neuron.v_mem),
+            ("ts_old", neuron.ts_old as f64),
+            ("v_th", neuron.v_th),
+        ]
+    }
 }
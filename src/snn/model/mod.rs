@@ -1,11 +1,17 @@
 //! Main `Model` trait for expanding this library to work with other models. Leaky integrate and fire is built in.
 
 use std::fmt::Debug;
+use std::ops::{BitAnd, BitOr, BitXor, Not};
 
-use self::{heap::HeapCalculator, lif::InjectionStruct};
+use self::{lif::{ComparatorInjection, InjectionStruct}, precision::{Precision, PrecisionHeap, WordLayout}};
+use super::fault::Fault;
+use super::mitigation::RangeCheck;
 
+pub mod ecc;
+pub mod fixed;
 pub mod heap;
 pub mod lif;
+pub mod precision;
 
 /// The main trait for neuron models.
 pub trait Model {
@@ -49,27 +55,101 @@ pub trait Model {
     fn use_v_mem_with_injection(neuron: &mut Self::Neuron, stuck: Stuck);
 
     /// Retrieves the heap calculator used by the neuron, if available.
-    fn get_heap(neuron: &Self::Neuron) -> Option<HeapCalculator<f64, u64>>;
+    fn get_heap(neuron: &Self::Neuron) -> Option<PrecisionHeap>;
 
     /// Retrieves the injection structure for the neuron, if available.
     fn get_injection_vmem(neuron: &Self::Neuron) -> Option<InjectionStruct>;
 
-    /// Configures the neuron to apply injection on the comparator.
+    /// Configures the neuron to apply injection on a random bit of one of the operands of the comparator.
     fn use_comparator(neuron: &mut Self::Neuron, stuck: Stuck);
 
     /// Retrieves the comparator for injection for the neuron, if available.
-    fn get_comparator(neuron: &Self::Neuron) -> Option<InjectionStruct>;
+    fn get_comparator(neuron: &Self::Neuron) -> Option<ComparatorInjection>;
+
+    /// Configures the neuron to apply a random failure mode to the logic resetting the membrane potential.
+    fn use_reset(neuron: &mut Self::Neuron, stuck: Stuck);
+
+    /// Configures the neuron to use a faulty multiplier in the leak computation.
+    fn use_multiplier(neuron: &mut Self::Neuron, stuck: Stuck);
+
+    /// Retrieves the multiplier used by the neuron, if available.
+    fn get_multiplier(neuron: &Self::Neuron) -> Option<PrecisionHeap>;
+
+    /// Configures the neuron to apply injection on the result of the exponential unit.
+    fn use_exp_unit(neuron: &mut Self::Neuron, stuck: Stuck);
+
+    /// Retrieves the injection structure for the exponential unit of the neuron, if available.
+    fn get_exp_unit(neuron: &Self::Neuron) -> Option<InjectionStruct>;
+
+    /// Reads the stored value of a parameter of the neuron ("v_th", "v_rest", "v_reset", "v_tau"),
+    /// or [None] if the model does not have it.
+    fn get_parameter(neuron: &Self::Neuron, parameter: &str) -> Option<f64>;
+
+    /// Overwrites the stored value of a parameter of the neuron. Unknown parameters are ignored.
+    fn set_parameter(neuron: &mut Self::Neuron, parameter: &str, val: f64);
+
+    /// Reads the word storing a parameter of the neuron, with the [Model::word_layout] of the neuron in
+    /// its lowest bits, or [None] if the model does not have it.
+    fn get_parameter_word(neuron: &Self::Neuron, parameter: &str) -> Option<u64>;
+
+    /// Overwrites the word storing a parameter of the neuron. Unknown parameters are ignored.
+    fn set_parameter_word(neuron: &mut Self::Neuron, parameter: &str, word: u64);
+
+    /// The state of the neuron sampled by the probes, as pairs of name and value: the membrane potential
+    /// ("v_mem"), the time of the last update ("ts_old") and any state specific to the model.
+    fn state(neuron: &Self::Neuron) -> Vec<(&'static str, f64)>;
+
+    /// Configures the neuron to check its membrane potential and threshold against plausible ranges.
+    fn use_range_check(neuron: &mut Self::Neuron, range_check: Option<RangeCheck>);
+
+    /// Sets the numeric precision of the neuron, rounding its parameters. It must be called before
+    /// injecting faults, so that they hit the bits of the reduced precision words.
+    fn use_precision(neuron: &mut Self::Neuron, precision: Precision);
+
+    /// Layout of the words storing the values of the neuron.
+    fn word_layout(neuron: &Self::Neuron) -> WordLayout;
+
+    /// Applies a fault at a given bit of a component of the neuron.
+    ///
+    /// # Arguments
+    ///
+    /// * `neuron` - The neuron hit by the fault.
+    /// * `fault` - The fault, whose component, bit and stuck type are used.
+    /// * `num_inputs` - The number of inputs of the neuron, used to build the heap of the full adder.
+    ///
+    /// # Returns
+    ///
+    /// An error if the component is not part of the neuron or the bit is out of range.
+    fn use_fault(neuron: &mut Self::Neuron, fault: &Fault, num_inputs: usize) -> Result<(), String>;
+
+    /// Removes a fault applied with [Model::use_fault], restoring its component as it is in the fault
+    /// free neuron. The state of the neuron, such as the membrane potential, is kept.
+    fn remove_fault(neuron: &mut Self::Neuron, fault: &Fault, fault_free: &Self::Neuron);
 
 }
 
 /// Enum representing different stuck values.
-#[derive(Debug, Clone)]
+///
+/// How long the fault lasts is given separately by [crate::snn::fault::FaultDuration].
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Stuck {
     Zero,
     One,
     Transient,
 }
 
+impl Stuck {
+    /// Parses a stuck type from its name, as written in the configuration files.
+    pub fn from_name(name: &str) -> Result<Stuck, String> {
+        match name.to_lowercase().as_str() {
+            "stuck_at_0" | "zero" | "z" | "0" => Ok(Stuck::Zero),
+            "stuck_at_1" | "one" | "o" | "1" => Ok(Stuck::One),
+            "transient_bit" | "transient" | "t" | "2" => Ok(Stuck::Transient),
+            _ => Err(format!("Invalid stuck type: {}", name)),
+        }
+    }
+}
+
 /// Trait for converting types to bits.
 pub trait ToBits<U> {
     /// Gets the bits representation of the type.
@@ -102,3 +182,90 @@ impl ToBits<u64> for f64 {
         64
     }
 }
+
+impl ToBits<u32> for i32 {
+    fn get_bits(&self) -> u32 {
+        *self as u32
+    }
+
+    fn from_bits(bits: u32) -> Self {
+        bits as i32
+    }
+
+    fn create_mask(&self, index: u64) -> u32 {
+        1u32 << index
+    }
+
+    fn num_bits(&self) -> u64 {
+        32
+    }
+}
+
+impl ToBits<u16> for i16 {
+    fn get_bits(&self) -> u16 {
+        *self as u16
+    }
+
+    fn from_bits(bits: u16) -> Self {
+        bits as i16
+    }
+
+    fn create_mask(&self, index: u64) -> u16 {
+        1u16 << index
+    }
+
+    fn num_bits(&self) -> u64 {
+        16
+    }
+}
+
+impl ToBits<u8> for i8 {
+    fn get_bits(&self) -> u8 {
+        *self as u8
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        bits as i8
+    }
+
+    fn create_mask(&self, index: u64) -> u8 {
+        1u8 << index
+    }
+
+    fn num_bits(&self) -> u64 {
+        8
+    }
+}
+
+impl ToBits<u128> for u128 {
+    fn get_bits(&self) -> u128 {
+        *self
+    }
+
+    fn from_bits(bits: u128) -> Self {
+        bits
+    }
+
+    fn create_mask(&self, index: u64) -> u128 {
+        1u128 << index
+    }
+
+    fn num_bits(&self) -> u64 {
+        128
+    }
+}
+
+/// Applies a stuck bit at the given index of the bit representation of a value.
+pub fn apply_stuck<T, U>(val: T, stuck: &Stuck, index: u64) -> T
+where
+    T: ToBits<U>,
+    U: BitAnd<Output = U> + BitOr<Output = U> + BitXor<Output = U> + Not<Output = U>,
+{
+    let bits = val.get_bits();
+    let mask = val.create_mask(index);
+    T::from_bits(match stuck {
+        Stuck::Zero => bits & !mask,
+        Stuck::One => bits | mask,
+        Stuck::Transient => bits ^ mask,
+    })
+}
@@ -0,0 +1,453 @@
+//! # Numeric Precision
+//!
+//! Accelerators for spiking neural networks often compute with reduced precision floating point numbers.
+//! This module defines the `Precision` of a network (`f64`, `f32`, `f16` or `bfloat16`), the `F16` and
+//! `Bf16` types implementing [ToBits] over `u16`, and the `PrecisionHeap`, a [HeapCalculator] working with
+//! the chosen precision.
+//!
+//! When a network uses a reduced precision every value is rounded to it after every operation, and the
+//! bit errors are injected in the bits of the reduced precision word.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::model::precision::{Bf16, Precision, F16};
+//!
+//! assert_eq!(F16::from_f64(1.0 / 3.0).to_f64(), 0.333251953125);
+//! assert_eq!(Bf16::from_f64(1.0 / 3.0).to_f64(), 0.333984375);
+//! assert_eq!(Precision::F16.round(65520.0), f64::INFINITY);
+//! assert_eq!(Precision::F16.num_bits(), 16);
+//! ```
+
+use std::fmt::{self, Display};
+use std::ops::{Add, Mul};
+
+use rand::Rng;
+
+use super::{apply_stuck, heap::HeapCalculator, Stuck, ToBits};
+
+/// Numeric precision used by the neurons and the weights of a network.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// IEEE 754 double precision (the default)
+    #[default]
+    F64,
+    /// IEEE 754 single precision
+    F32,
+    /// IEEE 754 half precision: 5 exponent bits and 10 mantissa bits
+    F16,
+    /// Brain floating point: 8 exponent bits and 7 mantissa bits
+    BF16,
+}
+
+/// IEEE 754 half precision number.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct F16(u16);
+
+/// Brain floating point number (the upper half of a `f32`).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bf16(u16);
+
+/// Field of a word a bit belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BitField {
+    Sign,
+    /// The exponent of a floating point word, or the integer bits of a fixed point one
+    Exponent,
+    /// The mantissa of a floating point word, or the fractional bits of a fixed point one
+    Mantissa,
+}
+
+/// Layout of a word: a sign bit on top, then `exponent_bits` bits and the mantissa in the lowest bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WordLayout {
+    pub num_bits: u64,
+    pub exponent_bits: u64,
+}
+
+/// Heap calculator in one of the supported precisions. Inputs and outputs are always `f64`.
+#[derive(Clone, Debug)]
+pub enum PrecisionHeap {
+    F64(HeapCalculator<f64, u64>),
+    F32(HeapCalculator<f32, u32>),
+    F16(HeapCalculator<F16, u16>),
+    BF16(HeapCalculator<Bf16, u16>),
+}
+
+impl Precision {
+    /// Number of bits of a value.
+    pub fn num_bits(&self) -> u64 {
+        match self {
+            Precision::F64 => 64,
+            Precision::F32 => 32,
+            Precision::F16 | Precision::BF16 => 16,
+        }
+    }
+
+    /// Layout of the words of this precision.
+    pub fn layout(&self) -> WordLayout {
+        let exponent_bits = match self {
+            Precision::F64 => 11,
+            Precision::F32 | Precision::BF16 => 8,
+            Precision::F16 => 5,
+        };
+        WordLayout {
+            num_bits: self.num_bits(),
+            exponent_bits,
+        }
+    }
+
+    /// Rounds a value to the nearest one representable in this precision.
+    pub fn round(&self, val: f64) -> f64 {
+        match self {
+            Precision::F64 => val,
+            Precision::F32 => val as f32 as f64,
+            Precision::F16 => F16::from_f64(val).to_f64(),
+            Precision::BF16 => Bf16::from_f64(val).to_f64(),
+        }
+    }
+
+    /// Sums the values, rounding every partial sum to this precision.
+    pub fn sum(&self, values: &[f64]) -> f64 {
+        match self {
+            Precision::F64 => values.iter().sum(),
+            _ => values.iter().fold(0.0, |acc, val| self.round(acc + val)),
+        }
+    }
+
+    /// Applies a stuck bit at the given index of the representation of a value in this precision.
+    pub fn stuck_bit(&self, val: f64, stuck: &Stuck, index: usize) -> f64 {
+        let index = index as u64;
+        match self {
+            Precision::F64 => apply_stuck(val, stuck, index),
+            Precision::F32 => apply_stuck(val as f32, stuck, index) as f64,
+            Precision::F16 => apply_stuck(F16::from_f64(val), stuck, index).to_f64(),
+            Precision::BF16 => apply_stuck(Bf16::from_f64(val), stuck, index).to_f64(),
+        }
+    }
+
+    /// The bits of the representation of a value in this precision, in the lowest bits of the word.
+    pub fn to_word(&self, val: f64) -> u64 {
+        match self {
+            Precision::F64 => val.to_bits(),
+            Precision::F32 => (val as f32).to_bits() as u64,
+            Precision::F16 => F16::from_f64(val).0 as u64,
+            Precision::BF16 => Bf16::from_f64(val).0 as u64,
+        }
+    }
+
+    /// The value represented by the lowest bits of a word in this precision, the inverse of [Precision::to_word].
+    pub fn from_word(&self, word: u64) -> f64 {
+        match self {
+            Precision::F64 => f64::from_bits(word),
+            Precision::F32 => f32::from_bits(word as u32) as f64,
+            Precision::F16 => F16(word as u16).to_f64(),
+            Precision::BF16 => Bf16(word as u16).to_f64(),
+        }
+    }
+
+    /// Applies a stuck bit at a random index of the representation of a value in this precision.
+    pub fn stuck_random_bit(&self, val: f64, stuck: &Stuck) -> f64 {
+        let random_bit_index = rand::thread_rng().gen_range(0..self.num_bits() as usize);
+        self.stuck_bit(val, stuck, random_bit_index)
+    }
+}
+
+impl WordLayout {
+    /// Field of the bit at the given index.
+    pub fn field(&self, bit: usize) -> BitField {
+        let bit = bit as u64;
+        if bit + 1 == self.num_bits {
+            BitField::Sign
+        } else if bit + 1 + self.exponent_bits >= self.num_bits {
+            BitField::Exponent
+        } else {
+            BitField::Mantissa
+        }
+    }
+
+    /// Indices of all the bits of a field.
+    pub fn bits(&self, field: BitField) -> Vec<usize> {
+        (0..self.num_bits as usize).filter(|bit| self.field(*bit) == field).collect()
+    }
+}
+
+impl F16 {
+    pub fn from_f64(val: f64) -> F16 {
+        F16(f64_to_small_float(val, 5, 10) as u16)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        small_float_to_f64(self.0 as u64, 5, 10)
+    }
+}
+
+impl Bf16 {
+    pub fn from_f64(val: f64) -> Bf16 {
+        Bf16(f64_to_small_float(val, 8, 7) as u16)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        small_float_to_f64(self.0 as u64, 8, 7)
+    }
+}
+
+impl PrecisionHeap {
+    /// Creates a new heap calculator in the given precision, with fault injection at a random position.
+    pub fn new(precision: Precision, dim: usize, stuck: Stuck) -> PrecisionHeap {
+        match precision {
+            Precision::F64 => PrecisionHeap::F64(HeapCalculator::new(dim, stuck)),
+            Precision::F32 => PrecisionHeap::F32(HeapCalculator::new(dim, stuck)),
+            Precision::F16 => PrecisionHeap::F16(HeapCalculator::new(dim, stuck)),
+            Precision::BF16 => PrecisionHeap::BF16(HeapCalculator::new(dim, stuck)),
+        }
+    }
+
+    /// Creates a new heap calculator in the given precision, with fault injection at a given bit of a given link.
+    pub fn with_fault(
+        precision: Precision,
+        dim: usize,
+        stuck: Stuck,
+        link: Option<usize>,
+        bit: u64,
+    ) -> Result<PrecisionHeap, String> {
+        Ok(match precision {
+            Precision::F64 => PrecisionHeap::F64(HeapCalculator::with_fault(dim, stuck, link, bit)?),
+            Precision::F32 => PrecisionHeap::F32(HeapCalculator::with_fault(dim, stuck, link, bit)?),
+            Precision::F16 => PrecisionHeap::F16(HeapCalculator::with_fault(dim, stuck, link, bit)?),
+            Precision::BF16 => PrecisionHeap::BF16(HeapCalculator::with_fault(dim, stuck, link, bit)?),
+        })
+    }
+
+    /// Creates a new multiplier of two operands in the given precision, with fault injection at a random
+    /// position.
+    pub fn multiplier(precision: Precision, stuck: Stuck) -> PrecisionHeap {
+        match precision {
+            Precision::F64 => PrecisionHeap::F64(HeapCalculator::multiplier(stuck)),
+            Precision::F32 => PrecisionHeap::F32(HeapCalculator::multiplier(stuck)),
+            Precision::F16 => PrecisionHeap::F16(HeapCalculator::multiplier(stuck)),
+            Precision::BF16 => PrecisionHeap::BF16(HeapCalculator::multiplier(stuck)),
+        }
+    }
+
+    /// Creates a new multiplier of two operands in the given precision, with fault injection at a given bit
+    /// of a given link.
+    pub fn multiplier_with_fault(
+        precision: Precision,
+        stuck: Stuck,
+        link: Option<usize>,
+        bit: u64,
+    ) -> Result<PrecisionHeap, String> {
+        Ok(match precision {
+            Precision::F64 => PrecisionHeap::F64(HeapCalculator::multiplier_with_fault(stuck, link, bit)?),
+            Precision::F32 => PrecisionHeap::F32(HeapCalculator::multiplier_with_fault(stuck, link, bit)?),
+            Precision::F16 => PrecisionHeap::F16(HeapCalculator::multiplier_with_fault(stuck, link, bit)?),
+            Precision::BF16 => PrecisionHeap::BF16(HeapCalculator::multiplier_with_fault(stuck, link, bit)?),
+        })
+    }
+
+    pub fn sum_all(&mut self, inputs: &[f64]) -> f64 {
+        match self {
+            PrecisionHeap::F64(heap) => heap.sum_all(inputs),
+            PrecisionHeap::F32(heap) => heap.sum_all(&convert(inputs, |v| v as f32)) as f64,
+            PrecisionHeap::F16(heap) => heap.sum_all(&convert(inputs, F16::from_f64)).to_f64(),
+            PrecisionHeap::BF16(heap) => heap.sum_all(&convert(inputs, Bf16::from_f64)).to_f64(),
+        }
+    }
+
+    pub fn multiply_all(&mut self, inputs: &[f64]) -> f64 {
+        match self {
+            PrecisionHeap::F64(heap) => heap.multiply_all(inputs),
+            PrecisionHeap::F32(heap) => heap.multiply_all(&convert(inputs, |v| v as f32)) as f64,
+            PrecisionHeap::F16(heap) => heap.multiply_all(&convert(inputs, F16::from_f64)).to_f64(),
+            PrecisionHeap::BF16(heap) => heap.multiply_all(&convert(inputs, Bf16::from_f64)).to_f64(),
+        }
+    }
+}
+
+fn convert<T>(inputs: &[f64], f: impl Fn(f64) -> T) -> Vec<T> {
+    inputs.iter().map(|v| f(*v)).collect()
+}
+
+/// Converts a `f64` to the bits of a smaller IEEE 754 format, rounding to the nearest even value.
+fn f64_to_small_float(val: f64, exp_bits: u32, mant_bits: u32) -> u64 {
+    let bits = val.to_bits();
+    let sign = (bits >> 63) << (exp_bits + mant_bits);
+    let max_exp = (1i64 << exp_bits) - 1;
+    let bias = (1i64 << (exp_bits - 1)) - 1;
+    let infinity = sign | ((max_exp as u64) << mant_bits);
+
+    if val.is_nan() {
+        return infinity | (1 << (mant_bits - 1));
+    }
+    if val.is_infinite() {
+        return infinity;
+    }
+    let exp_field = ((bits >> 52) & 0x7ff) as i64;
+    if exp_field == 0 {
+        // zero or f64 subnormal: far below the smallest value of the smaller format
+        return sign;
+    }
+
+    let significand = (bits & ((1u64 << 52) - 1)) | (1u64 << 52);
+    let mut exp = exp_field - 1023 + bias;
+
+    if exp <= 0 {
+        // subnormal in the smaller format (a carry in the rounding gives the smallest normal)
+        let shift = (52 - mant_bits as i64 + 1 - exp) as u32;
+        return sign | round_shift(significand, shift);
+    }
+
+    let mut rounded = round_shift(significand, 52 - mant_bits);
+    if rounded >> (mant_bits + 1) != 0 {
+        rounded >>= 1;
+        exp += 1;
+    }
+    if exp >= max_exp {
+        return infinity;
+    }
+    sign | ((exp as u64) << mant_bits) | (rounded & ((1u64 << mant_bits) - 1))
+}
+
+/// Converts the bits of a smaller IEEE 754 format to a `f64` (always exact).
+fn small_float_to_f64(bits: u64, exp_bits: u32, mant_bits: u32) -> f64 {
+    let negative = (bits >> (exp_bits + mant_bits)) & 1 == 1;
+    let exp_field = ((bits >> mant_bits) & ((1u64 << exp_bits) - 1)) as i32;
+    let mant = bits & ((1u64 << mant_bits) - 1);
+    let max_exp = (1i32 << exp_bits) - 1;
+    let bias = (1i32 << (exp_bits - 1)) - 1;
+
+    let abs = if exp_field == max_exp {
+        if mant == 0 {
+            f64::INFINITY
+        } else {
+            f64::NAN
+        }
+    } else if exp_field == 0 {
+        mant as f64 * 2f64.powi(1 - bias - mant_bits as i32)
+    } else {
+        (mant | (1u64 << mant_bits)) as f64 * 2f64.powi(exp_field - bias - mant_bits as i32)
+    };
+
+    if negative {
+        -abs
+    } else {
+        abs
+    }
+}
+
+/// Shifts right by `shift` bits, rounding to the nearest even value.
+fn round_shift(val: u64, shift: u32) -> u64 {
+    if shift == 0 {
+        return val;
+    }
+    if shift >= 64 {
+        return 0;
+    }
+    let res = val >> shift;
+    let rest = val & ((1u64 << shift) - 1);
+    let half = 1u64 << (shift - 1);
+    if rest > half || (rest == half && res & 1 == 1) {
+        res + 1
+    } else {
+        res
+    }
+}
+
+impl Add for F16 {
+    type Output = F16;
+
+    fn add(self, rhs: F16) -> F16 {
+        F16::from_f64(self.to_f64() + rhs.to_f64())
+    }
+}
+
+impl Mul for F16 {
+    type Output = F16;
+
+    fn mul(self, rhs: F16) -> F16 {
+        F16::from_f64(self.to_f64() * rhs.to_f64())
+    }
+}
+
+impl Add for Bf16 {
+    type Output = Bf16;
+
+    fn add(self, rhs: Bf16) -> Bf16 {
+        Bf16::from_f64(self.to_f64() + rhs.to_f64())
+    }
+}
+
+impl Mul for Bf16 {
+    type Output = Bf16;
+
+    fn mul(self, rhs: Bf16) -> Bf16 {
+        Bf16::from_f64(self.to_f64() * rhs.to_f64())
+    }
+}
+
+impl Display for F16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+impl Display for Bf16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+impl ToBits<u32> for f32 {
+    fn get_bits(&self) -> u32 {
+        self.to_bits()
+    }
+
+    fn from_bits(bits: u32) -> Self {
+        f32::from_bits(bits)
+    }
+
+    fn create_mask(&self, index: u64) -> u32 {
+        1u32 << index
+    }
+
+    fn num_bits(&self) -> u64 {
+        32
+    }
+}
+
+impl ToBits<u16> for F16 {
+    fn get_bits(&self) -> u16 {
+        self.0
+    }
+
+    fn from_bits(bits: u16) -> Self {
+        F16(bits)
+    }
+
+    fn create_mask(&self, index: u64) -> u16 {
+        1u16 << index
+    }
+
+    fn num_bits(&self) -> u64 {
+        16
+    }
+}
+
+impl ToBits<u16> for Bf16 {
+    fn get_bits(&self) -> u16 {
+        self.0
+    }
+
+    fn from_bits(bits: u16) -> Self {
+        Bf16(bits)
+    }
+
+    fn create_mask(&self, index: u64) -> u16 {
+        1u16 << index
+    }
+
+    fn num_bits(&self) -> u64 {
+        16
+    }
+}
@@ -64,6 +64,18 @@
 //! // Access the results using the 'shared_output' Arc.
 //! ```
 //!
+//! `solve_threaded` runs every layer in its own thread, and `solve_multiple_vec_spike` wraps its output in the `Arc`
+//! above. `solve_sequential` gives the same output running all the layers in the calling thread, `solve_event_driven`
+//! handles a priority queue of spike events updating only the neurons that receive a spike, `solve_clock_driven` updates
+//! every neuron at every tick of a clock, and `solve` takes the engine (`SimulationMode`) as a parameter. All of them
+//! return a `SimulationOutput` with the spike trains of the output neurons. `stream` reads the input spikes from an
+//! iterator and returns the output spikes while they are produced, for long or unbounded inputs.
+//! `session` keeps the state of the neurons between segments of input, until it is reset, and `solve_batch` solves
+//! many inputs on a pool of worker threads.
+//!
+//! A spike fired at time `ts` reaches the next layer at `ts + 1`, unless `use_delays` sets the delays of the input
+//! synapses of a layer: with a delay `d` it reaches the neuron at `ts + d`.
+//!
 //! ## Resilience Mechanisms
 //!
 //! The library includes support for resilience mechanisms. Specifics of the resilience handling are not provided in this documentation,
@@ -71,14 +83,73 @@
 //!
 
 
+use crate::snn::drift::Drift;
+use crate::snn::fault::Fault;
 use crate::snn::layer::Layer;
+use crate::snn::mitigation::RangeCheck;
+use crate::snn::model::fixed::QFormat;
+use crate::snn::model::precision::Precision;
+use crate::snn::output::SimulationOutput;
+use crate::snn::probe::ProbeTrace;
+use crate::snn::recorder::SpikeRecorder;
+use crate::snn::session::Session;
+use crate::snn::stream::SpikeStream;
 use crate::Model;
 use nalgebra::DMatrix;
+use rand::Rng;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 
 use super::Spike;
 
+/// Ticks of the clock of [NN::solve_clock_driven], from the first input spike.
+pub const MAX_CLOCK_TICKS: u128 = 1 << 20;
+
+/// Engine simulating a neural network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimulationMode {
+    /// One thread per layer, passing the spikes to the next layer through a channel
+    Threaded,
+    /// All the layers in the calling thread, one after the other: deterministic and without the cost
+    /// of spawning the threads, it is the default of the resilience campaigns
+    #[default]
+    Sequential,
+    /// A priority queue of spike events, updating only the neurons that receive a spike
+    EventDriven,
+    /// A clock of period `dt`: all the neurons integrate and leak at every tick
+    ClockDriven { dt: u128 },
+}
+
+impl FromStr for SimulationMode {
+    type Err = String;
+
+    /// Parses the name of an engine; the period of the clock follows a colon, e.g. "clock-driven:5".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((mode, dt)) = s.split_once(':') {
+            return match mode.parse()? {
+                SimulationMode::ClockDriven { .. } => dt
+                    .trim()
+                    .parse()
+                    .map(|dt| SimulationMode::ClockDriven { dt })
+                    .map_err(|_| format!("Invalid clock period: {}", dt)),
+                _ => Err(format!("Invalid simulation mode: {}", s)),
+            };
+        }
+        match s.trim().to_lowercase().as_str() {
+            "threaded" | "threads" | "t" => Ok(SimulationMode::Threaded),
+            "sequential" | "single-threaded" | "single threaded" | "s" => Ok(SimulationMode::Sequential),
+            "event-driven" | "event driven" | "events" | "e" => Ok(SimulationMode::EventDriven),
+            "clock-driven" | "clock driven" | "clock" | "c" => Ok(SimulationMode::ClockDriven { dt: 1 }),
+            _ => Err(format!("Invalid simulation mode: {}", s)),
+        }
+    }
+}
+
 /// Neural Network (`NN`) structure representing a collection of layers.
 /// The neural network is defined by the type parameter `M`, which must implement
 /// the `Model` trait.
@@ -158,6 +229,15 @@ impl<M: Model + Clone> NN<M> {
             neurons,
             input_weights,
             intra_weights,
+            delays: None,
+            range_check: None,
+            weight_format: None,
+            precision: Precision::F64,
+            faults: vec![],
+            transients: vec![],
+            recorder: None,
+            probes: vec![],
+            drift: None,
         };
         self.layers.push(new_layer);
 
@@ -166,6 +246,125 @@ impl<M: Model + Clone> NN<M> {
     pub fn get_num_layers(&self) -> usize {
         return self.layers.len();
     }
+
+    /// Stores the weights of all the layers as fixed point words of the given format.
+    pub fn use_fixed_weights(&mut self, format: QFormat) {
+        for layer in self.layers.iter_mut() {
+            layer.use_fixed_weights(format);
+        }
+    }
+
+    /// Sets the numeric precision of the weights and of the neurons of all the layers.
+    pub fn use_precision(&mut self, precision: Precision) {
+        for layer in self.layers.iter_mut() {
+            layer.use_precision(precision);
+        }
+    }
+
+    /// Applies a drift to the parameters of all the layers of the network.
+    pub fn use_drift(&mut self, drift: &Drift) {
+        self.use_drift_with_rng(drift, &mut rand::thread_rng());
+    }
+
+    /// Applies a drift to all the layers of the network as [NN::use_drift], drawing the Gaussian deviations
+    /// from the given random number generator, so that a seeded generator always gives the same network.
+    pub fn use_drift_with_rng<R: Rng>(&mut self, drift: &Drift, rng: &mut R) {
+        for layer in self.layers.iter_mut() {
+            layer.use_drift_with_rng(drift, rng);
+        }
+    }
+
+    /// Sets the delays of the input synapses of a layer: a spike fired at time `ts` reaches a neuron of
+    /// the layer at `ts + delay`, an input spike at `ts + delay - 1`. Without delays all of them are 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The index of the layer.
+    /// * `delays` - Matrix of the delays in time steps, with the dimensions of the input weights.
+    ///
+    /// # Returns
+    ///
+    /// An error if the layer does not exist, the dimensions are not the ones of the input weights or a
+    /// delay is 0.
+    pub fn use_delays(&mut self, layer: usize, delays: DMatrix<u128>) -> Result<(), String> {
+        let delayed = self
+            .layers
+            .get_mut(layer)
+            .ok_or(format!("No layer {} in the network", layer))?;
+        if delays.shape() != delayed.input_weights.shape() {
+            return Err("Incompatible delay matrix".to_string());
+        }
+        if delays.iter().any(|delay| *delay == 0) {
+            return Err("The delays should be at least 1".to_string());
+        }
+        delayed.delays = Some(delays);
+        Ok(())
+    }
+
+    /// True if a layer has delays of the input synapses.
+    fn has_delays(&self) -> bool {
+        self.layers.iter().any(|layer| layer.delays.is_some())
+    }
+
+    /// Schedules a fault of a fault list in its layer: it is injected when the simulation reaches its time.
+    ///
+    /// # Returns
+    ///
+    /// An error if the layer, the neuron, the weight or the bit of the fault do not exist.
+    pub fn schedule_fault(&mut self, fault: Fault) -> Result<(), String> {
+        let layer = self
+            .layers
+            .get_mut(fault.layer)
+            .ok_or(format!("No layer {} in the network", fault.layer))?;
+        // check the fault on a copy of the layer, so that an invalid one is rejected right away
+        layer.clone().inject_fault(&fault)?;
+        layer.schedule_fault(fault);
+        Ok(())
+    }
+
+    /// Enables the range check mitigation on all the layers of the network. All the layers share the
+    /// counters of `range_check`.
+    pub fn use_range_check(&mut self, range_check: &RangeCheck) {
+        for layer in self.layers.iter_mut() {
+            layer.use_range_check(Some(range_check.clone()));
+        }
+    }
+
+    /// Samples the state of a neuron at every update, in all the following simulations. The samples are
+    /// returned with the output of the simulation.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The layer of the neuron.
+    /// * `neuron` - The index of the neuron in the layer.
+    ///
+    /// # Returns
+    ///
+    /// An error if the neuron does not exist.
+    pub fn add_probe(&mut self, layer: usize, neuron: usize) -> Result<(), String> {
+        let probed = self
+            .layers
+            .get_mut(layer)
+            .ok_or(format!("No layer {} in the network", layer))?;
+        if neuron >= probed.num_neurons() {
+            return Err(format!("No neuron {} in layer {}", neuron, layer));
+        }
+        if !probed.probes.iter().any(|trace| trace.neuron == neuron) {
+            probed.probes.push(ProbeTrace::new(layer, neuron));
+        }
+        Ok(())
+    }
+
+    /// Records the spikes fired by the layers of the network with `recorder`, in all the following
+    /// simulations. The spikes of every simulation are returned with its output, see
+    /// [SimulationOutput::recording].
+    pub fn use_recorder(&mut self, mut recorder: SpikeRecorder) {
+        recorder.layer_sizes = self.layers.iter().map(|layer| layer.num_neurons()).collect();
+        recorder.spikes.clear();
+        for layer in self.layers.iter_mut() {
+            layer.recorder = Some(recorder.clone());
+        }
+    }
     /*
        Ho un vettore di spike iniziali.
        Inizialmente si crea il primo thread responsabile di gestire il primo vettore di spike e quindi il primo
@@ -300,6 +499,8 @@ impl<M: Model + Clone> NN<M> {
     /// Solves the SNN given a vector of Tuples (neuron_id, vectors of spikes) -> (Vec<(u128, Vec<u128>)>).
     /// Each spike is referred to a single input neuron, providing reduced visibility to neurons in each layer.
     ///
+    /// Kept for the existing callers: [NN::solve_threaded] returns the same output as a [SimulationOutput].
+    ///
     /// # Arguments
     ///
     /// * `input` - Vector of tuples where each tuple represents the neuron_id and the corresponding vector of spikes.
@@ -337,8 +538,24 @@ impl<M: Model + Clone> NN<M> {
         &mut self,
         input: Vec<(u128, Vec<u128>)>,
     ) -> Arc<Mutex<Vec<(u128, Vec<u128>)>>> {
+        Arc::new(Mutex::new(self.solve_threaded(input).into_vec()))
+    }
+
+    /// Solves the neural network with a thread per layer, passing the spikes to the next layer through a
+    /// channel. A network with delays of the synapses is solved by [NN::solve_sequential].
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Vector of tuples representing neuron indices and their corresponding vectors of spikes.
+    ///
+    /// # Returns
+    ///
+    /// The spike trains of the output neurons.
+    pub fn solve_threaded(&self, input: Vec<(u128, Vec<u128>)>) -> SimulationOutput {
+        if self.has_delays() {
+            return self.solve_sequential(input);
+        }
         //println!("Enter solve multiple vec spike");
-        let mut index_input: usize = 0;
         // creo tanti canali quanti sono i layer
         let num_layers = self.get_num_layers();
 
@@ -346,6 +563,10 @@ impl<M: Model + Clone> NN<M> {
         for i in 0..self.layers.last().unwrap().num_neurons() {
             shared_output.lock().unwrap().push((i as u128, vec![]));
         }
+        // without input spikes no neuron fires
+        if input.iter().all(|(_, spikes)| spikes.is_empty()) {
+            return SimulationOutput::new(self.layers.last().unwrap().num_neurons());
+        }
 
         // let mut output:Vec<(u128, Vec<u128>)> = vec![];
         let mut channel_tx = vec![];
@@ -363,7 +584,8 @@ impl<M: Model + Clone> NN<M> {
         // Creazione dei thread
         let mut handles = vec![];
         let input_spikes = Spike::vec_of_all_spikes(input);
-        let duration = input_spikes.last().clone().unwrap().ts as usize;
+        // timestamps may be anywhere in the u128 range, e.g. after a fault in the input encoding
+        let duration = input_spikes.last().clone().unwrap().ts;
         for layer_idx in 0..num_layers {
             let next_tx = if layer_idx < num_layers - 1 {
                 Some(channel_tx[layer_idx + 1].clone())
@@ -377,15 +599,13 @@ impl<M: Model + Clone> NN<M> {
             let handle = thread:://Builder::new()
             /*.name(thread_name) // Imposta il nome del thread*/
             spawn(move || {
-                // il vettore di spike da passare al thread successivo
-                let mut layer_output: Vec<Spike> = vec![]; // Output del layer (Spike generati)
                 let mut ts: u128 = 0;
                 // let mut counter: u128 = 0;
                 let mut neuron_counters: Vec<u128> = vec![];
                 for _ in 0..layers[layer_idx].num_neurons() {
                     neuron_counters.push(0);
                 }
-                while ts <= duration as u128 + layer_idx as u128 {
+                while ts <= duration.saturating_add(layer_idx as u128) {
                     // Ricezione degli spike dal layer precedente
                     let input_spike = match rx.recv() {
                         Ok(input_spike) => input_spike,
@@ -409,52 +629,24 @@ impl<M: Model + Clone> NN<M> {
                         }
                     }
 
-                    //println!("input_spikes: {:?} (Thread: {})",input_spike,thread_name);
-                    //println!("input ts: {}, layer_idx: {}",input_spike[0].ts.clone(), layer_idx.clone());
-                    //ts=input_spike[0].ts-layer_idx as u128;
-                    //println!("Receive vec: {:?}",input_spike);
                     // eseguo i calcoli per aggiornare le tensioni di membrana e riempio il layer_output di spike
-                    for neuron_idx in 0..layers[layer_idx].num_neurons() as u128 {
-                        let sum: f64;
-                        let input_spike_tmp = input_spike.clone();
-                        let mut s = 0;
-                        if !input_spike_tmp.is_empty() {
-                            s = input_spike_tmp.clone().get(0).unwrap().ts;
-                        }
-
-                        // println!("\n\n\n\n input spike: {:?}, neuron_idx: {}, matrix: {}",input_spike_tmp.clone(), neuron_idx, &layers[layer_idx].input_weights);
-
-                        //do the sum considering the possible errors
-                        sum = Self::calculate_sum(input_spike_tmp, &layers[layer_idx], neuron_idx);
-
-                        /*for spike in input_spike_tmp.into_iter(){
-                            sum += 1.0*layers[layer_idx].input_weights[(spike.neuron_id, neuron_idx as usize)]; // da reimplementare in una funzione a parte con gli stuck
-                        }
-                        */
-                        let res = M::handle_spike(
-                            layers[layer_idx]
-                                .get_neuron_mut(neuron_idx as usize)
-                                .unwrap(),
-                            sum,
-                            s,
-                        );
-                        if res == 1.0 {
-                            layer_output.push(Spike::new(
-                                input_spike[0].ts + 1,
-                                layer_idx,
-                                neuron_idx as usize,
-                            ))
-                        }
-                    }
+                    let layer_output = Self::step_layer(
+                        &mut layers[layer_idx],
+                        layer_idx,
+                        &input_spike,
+                        input_spike[0].ts,
+                        input_spike[0].ts + 1,
+                        next_tx.is_none(),
+                        false,
+                    );
 
                     // Invio dei nuovi spike al layer successivo (se presente)
                     if let Some(next_tx) = &next_tx {
                         if !layer_output.is_empty() {
                             //println!("vec in output: {:?} sent from thread:{}",layer_output.clone(),thread_name);
                             next_tx
-                                .send(layer_output.clone())
+                                .send(layer_output)
                                 .expect("Error sending the vector of spikes");
-                            layers[layer_idx].update_layer_cycle(&layer_output);
                         }
                     } else {
                         for s in layer_output.iter() {
@@ -471,53 +663,421 @@ impl<M: Model + Clone> NN<M> {
                             neuron_counters[s.neuron_id] += 1;
                         }
                     }
-                    layer_output = vec![];
                     ts += 1;
                 } // end while
 
                 // Salvataggio degli spike generati dal layer nell'array
                 //let mut layer_data = layer_clone[layer_idx].lock().unwrap();
                 //layer_data.extend(layer_output);
+
+                // the samples of the probed neurons and the recorded spikes of the layer
+                (std::mem::take(&mut layers[layer_idx].probes), layers[layer_idx].recorder.take())
             }); // end thread
             handles.push(handle);
         }
-        let mut ts: usize = 0;
-        while ts <= duration {
-            let spike = match input_spikes.get(index_input) {
-                Some(value) => value.clone(),
-                None => Spike::new(0, 0, 0),
-            };
-            ts = spike.ts as usize;
-            let mut vec_of_spikes = vec![];
-            // println!("input spikes to starting: {:?}",input_spikes);
-            for _ in 0..self.layers[0].num_neurons() {
-                let s = match input_spikes.get(index_input) {
-                    Some(value) => value.clone(),
-                    None => Spike::new(0, 0, 0),
-                };
-                if s.ts == spike.ts {
-                    vec_of_spikes.push(s);
-                    index_input += 1;
-                    //println!("Emit spike: {}",spike);
-                } else {
-                    break;
-                }
+        // the spikes of every timestamp, at most one per input neuron in every vector
+        for same_ts in input_spikes.chunk_by(|a, b| a.ts == b.ts) {
+            for vec_of_spikes in same_ts.chunks(self.layers[0].num_neurons().max(1)) {
+                first_tx
+                    .send(vec_of_spikes.to_vec())
+                    .expect("Error sending the vector of spikes");
             }
-            first_tx
-                .send(vec_of_spikes.clone())
-                .expect("Error sending the vector of spikes");
-            //println!("Sent vec: {:?} to T0",vec_of_spikes);
-            ts += 1;
         }
         first_tx
             .send(vec![])
             .expect("Error sending the vector of spikes");
 
         // Attendo il completamento di tutti i thread
+        let mut probes = vec![];
+        let mut recorders = vec![];
         for handle in handles {
-            handle.join().expect("Ok"); //.expect("Failed to join a thread");
+            let (layer_probes, recorder) = handle.join().expect("Ok"); //.expect("Failed to join a thread");
+            probes.extend(layer_probes);
+            recorders.extend(recorder);
+        }
+        let mut output = SimulationOutput::from(shared_output.lock().unwrap().clone());
+        output.probes = probes;
+        output.recording = SpikeRecorder::merge(recorders);
+        output
+    }
+
+    /// Solves the neural network in the calling thread, one layer after the other. The output is the
+    /// same of [NN::solve_threaded], without spawning a thread per layer. With delays of the synapses the
+    /// spikes are handled in the order of arrival, as in a [Session].
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Vector of tuples representing neuron indices and their corresponding vectors of spikes.
+    ///
+    /// # Returns
+    ///
+    /// The spike trains of the output neurons.
+    pub fn solve_sequential(&self, input: Vec<(u128, Vec<u128>)>) -> SimulationOutput {
+        if self.has_delays() {
+            let mut session = self.session();
+            session.feed(input);
+            return session.run();
+        }
+        let mut output = SimulationOutput::new(self.layers.last().unwrap().num_neurons());
+        // without input spikes no neuron fires
+        if input.iter().all(|(_, spikes)| spikes.is_empty()) {
+            return output;
+        }
+
+        let input_spikes = Spike::vec_of_all_spikes(input);
+        let duration = input_spikes.last().unwrap().ts;
+        // the vectors of spikes received by the layer, as sent to the first layer by the threaded solver
+        let mut messages: Vec<Vec<Spike>> = input_spikes
+            .chunk_by(|a, b| a.ts == b.ts)
+            .flat_map(|same_ts| same_ts.chunks(self.layers[0].num_neurons().max(1)))
+            .map(|vec_of_spikes| vec_of_spikes.to_vec())
+            .collect();
+
+        let num_layers = self.get_num_layers();
+        let mut layers = self.layers.clone();
+        for (layer_idx, layer) in layers.iter_mut().enumerate() {
+            let is_last = layer_idx == num_layers - 1;
+            // a layer thread handles at most this many vectors of spikes
+            let limit = usize::try_from(duration.saturating_add(layer_idx as u128))
+                .map_or(usize::MAX, |limit| limit.saturating_add(1));
+            let mut next_messages = vec![];
+            for input_spike in messages.iter().take(limit) {
+                let ts = input_spike[0].ts;
+                let layer_output = Self::step_layer(layer, layer_idx, input_spike, ts, ts + 1, is_last, false);
+                if !is_last {
+                    if !layer_output.is_empty() {
+                        next_messages.push(layer_output);
+                    }
+                } else {
+                    for s in layer_output {
+                        output.push(s.neuron_id, s.ts);
+                    }
+                }
+            }
+            messages = next_messages;
         }
-        return shared_output;
+        output.recording = SpikeRecorder::merge(layers.iter_mut().filter_map(|layer| layer.recorder.take()));
+        output.probes = layers.into_iter().flat_map(|layer| layer.probes).collect();
+        output
+    }
+
+    /// Solves the neural network with the given simulation engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Vector of tuples representing neuron indices and their corresponding vectors of spikes.
+    /// * `mode` - The simulation engine.
+    ///
+    /// # Returns
+    ///
+    /// The spike trains of the output neurons.
+    pub fn solve(&self, input: Vec<(u128, Vec<u128>)>, mode: SimulationMode) -> SimulationOutput {
+        match mode {
+            SimulationMode::Threaded => self.solve_threaded(input),
+            SimulationMode::Sequential => self.solve_sequential(input),
+            SimulationMode::EventDriven => self.solve_event_driven(input),
+            SimulationMode::ClockDriven { dt } => self.solve_clock_driven(input, dt),
+        }
+    }
+
+    /// Creates a session simulating the neural network in segments, from the initial state of its neurons (see
+    /// [session](crate::snn::session)).
+    pub fn session(&self) -> Session<M> {
+        Session::new(self)
+    }
+
+    /// Simulates the neural network on a stream of input spikes, in the calling thread (see
+    /// [stream](crate::snn::stream)).
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input spikes sorted by time, e.g. from the receiver of a channel.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the spikes of the output neurons, produced while the input is read.
+    pub fn stream<I: IntoIterator<Item = Spike>>(&self, input: I) -> SpikeStream<M, I::IntoIter> {
+        SpikeStream::new(self.session(), input.into_iter())
+    }
+
+    /// Solves the neural network on many inputs with a pool of worker threads. Every worker takes the next
+    /// input when it is done with the previous one, so no thread is spawned for a single input, unless the
+    /// engine is [SimulationMode::Threaded]. Every input is simulated on its own clone of the layers, as in
+    /// [NN::solve].
+    ///
+    /// With a recorder (see [NN::use_recorder]) every output holds the spikes recorded for its own input.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The inputs, each one a vector of tuples of neuron index and spike times.
+    /// * `mode` - The simulation engine of every input.
+    /// * `workers` - The number of worker threads, at least 1.
+    ///
+    /// # Returns
+    ///
+    /// The outputs of the network, in the order of the inputs.
+    pub fn solve_batch(
+        &self,
+        inputs: &[Vec<(u128, Vec<u128>)>],
+        mode: SimulationMode,
+        workers: usize,
+    ) -> Vec<SimulationOutput> {
+        let workers = workers.clamp(1, inputs.len().max(1));
+        // index of the next input to solve
+        let next = AtomicUsize::new(0);
+        let mut outputs: Vec<(usize, SimulationOutput)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    let next = &next;
+                    scope.spawn(move || {
+                        let mut solved = vec![];
+                        loop {
+                            let idx = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(input) = inputs.get(idx) else { break };
+                            solved.push((idx, self.solve(input.clone(), mode)));
+                        }
+                        solved
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("Failed to join a worker"))
+                .collect()
+        });
+        outputs.sort_by_key(|(idx, _)| *idx);
+        outputs.into_iter().map(|(_, output)| output).collect()
+    }
+
+    /// Solves the neural network with a priority queue of spike events, in the calling thread.
+    ///
+    /// The queue holds the spikes ordered by arrival time and receiving layer: all the spikes received by
+    /// a layer at the same time are handled together, and only the neurons with a synapse from a spiking
+    /// neuron are updated. A neuron without input leaks all at once at its next input, so with sparse
+    /// activity the cost follows the number of spikes instead of the size of the network. The output is
+    /// the one of the other engines up to the rounding of the leak, and to the faults applied at every
+    /// update of a neuron, such as a stuck bit of its membrane potential.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Vector of tuples representing neuron indices and their corresponding vectors of spikes.
+    ///
+    /// # Returns
+    ///
+    /// The spike trains of the output neurons.
+    pub fn solve_event_driven(&self, input: Vec<(u128, Vec<u128>)>) -> SimulationOutput {
+        let mut output = SimulationOutput::new(self.layers.last().unwrap().num_neurons());
+
+        let num_layers = self.get_num_layers();
+        let mut layers = self.layers.clone();
+        // every event is a spike with its arrival time and the receiving layer
+        let mut queue: BinaryHeap<Reverse<(u128, usize, Spike)>> = BinaryHeap::new();
+        for spike in Spike::vec_of_all_spikes(input) {
+            Self::schedule(&mut queue, &layers[0], 0, spike);
+        }
+        while let Some(Reverse((ts, layer_idx, first))) = queue.pop() {
+            let mut input_spike = vec![first];
+            while let Some(Reverse((next_ts, next_layer, _))) = queue.peek() {
+                if *next_ts != ts || *next_layer != layer_idx {
+                    break;
+                }
+                input_spike.push(queue.pop().unwrap().0 .2);
+            }
+
+            let is_last = layer_idx == num_layers - 1;
+            let layer_output = Self::step_layer(&mut layers[layer_idx], layer_idx, &input_spike, ts, ts + 1, is_last, true);
+            for s in layer_output {
+                if is_last {
+                    output.push(s.neuron_id, s.ts);
+                } else {
+                    Self::schedule(&mut queue, &layers[layer_idx + 1], layer_idx + 1, s);
+                }
+            }
+        }
+        output.recording = SpikeRecorder::merge(layers.iter_mut().filter_map(|layer| layer.recorder.take()));
+        output.probes = layers.into_iter().flat_map(|layer| layer.probes).collect();
+        output
+    }
+
+    /// Solves the neural network with a clock of period `dt`, in the calling thread.
+    ///
+    /// At every tick all the neurons integrate the spikes received since the previous tick and leak, also
+    /// without input, so a neuron can fire between two input spikes as in a synchronous neuromorphic chip.
+    /// The input spikes are received at the first tick not earlier than their time, and a spike fired at a
+    /// tick reaches the next layer at the following one, or after as many ticks as the delay of the synapse.
+    /// The clock runs from the first input spike until the last one has crossed all the layers, so the
+    /// cost grows with the time span of the input: it stops after [MAX_CLOCK_TICKS] ticks, and the input
+    /// spikes received later are dropped, such as a spike moved far away by a fault of its timestamp. With
+    /// `dt` equal to 1 the spikes have the same times of the other engines.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Vector of tuples representing neuron indices and their corresponding vectors of spikes.
+    /// * `dt` - The period of the clock, at least 1.
+    ///
+    /// # Returns
+    ///
+    /// The spike trains of the output neurons.
+    pub fn solve_clock_driven(&self, input: Vec<(u128, Vec<u128>)>, dt: u128) -> SimulationOutput {
+        let mut output = SimulationOutput::new(self.layers.last().unwrap().num_neurons());
+        // without input spikes no neuron fires
+        if input.iter().all(|(_, spikes)| spikes.is_empty()) {
+            return output;
+        }
+
+        let dt = dt.max(1);
+        let num_layers = self.get_num_layers();
+        let mut input_spikes = Spike::vec_of_all_spikes(input);
+        let first_tick = input_spikes[0].ts.div_ceil(dt);
+        let max_tick = first_tick.saturating_add(MAX_CLOCK_TICKS - 1);
+        input_spikes.retain(|spike| spike.ts.div_ceil(dt) <= max_tick);
+        // the longest delay of every layer, in ticks
+        let crossing: u128 = self
+            .layers
+            .iter()
+            .map(|layer| layer.delays.as_ref().map_or(1, |delays| delays.max()))
+            .sum();
+        let last_tick = input_spikes
+            .last()
+            .unwrap()
+            .ts
+            .div_ceil(dt)
+            .saturating_add(crossing)
+            .min(max_tick);
+
+        let mut layers = self.layers.clone();
+        // the spikes received by every layer, by time of the tick they arrive at
+        let mut pending: Vec<BTreeMap<u128, Vec<Spike>>> = vec![BTreeMap::new(); num_layers];
+        for spike in input_spikes {
+            for delay in layers[0].delays_from(spike.neuron_id) {
+                let arrival = spike.ts.saturating_add((delay - 1).saturating_mul(dt)).div_ceil(dt).saturating_mul(dt);
+                pending[0].entry(arrival).or_default().push(spike.clone());
+            }
+        }
+        for tick in first_tick..=last_tick {
+            let ts = tick.saturating_mul(dt);
+            for layer_idx in 0..num_layers {
+                let is_last = layer_idx == num_layers - 1;
+                let received = pending[layer_idx].remove(&ts).unwrap_or_default();
+                let layer_output =
+                    Self::step_layer(&mut layers[layer_idx], layer_idx, &received, ts, ts.saturating_add(dt), is_last, false);
+                for s in layer_output {
+                    if is_last {
+                        output.push(s.neuron_id, s.ts);
+                    } else {
+                        for delay in layers[layer_idx + 1].delays_from(s.neuron_id) {
+                            let arrival = s.ts.saturating_add((delay - 1).saturating_mul(dt));
+                            pending[layer_idx + 1].entry(arrival).or_default().push(s.clone());
+                        }
+                    }
+                }
+            }
+        }
+        output.recording = SpikeRecorder::merge(layers.iter_mut().filter_map(|layer| layer.recorder.take()));
+        output.probes = layers.into_iter().flat_map(|layer| layer.probes).collect();
+        output
+    }
+
+    /// Adds to a queue of spike events the arrivals of a spike at a layer, one for each delay of the
+    /// synapses from its neuron.
+    pub(crate) fn schedule(
+        queue: &mut BinaryHeap<Reverse<(u128, usize, Spike)>>,
+        layer: &Layer<M>,
+        layer_idx: usize,
+        spike: Spike,
+    ) {
+        for delay in layer.delays_from(spike.neuron_id) {
+            queue.push(Reverse((spike.ts.saturating_add(delay - 1), layer_idx, spike.clone())));
+        }
+    }
+
+    /// Handles the spikes received by a layer at time `ts`: injects the faults and the drift up to that
+    /// time, updates the membrane of the neurons and returns the generated spikes, at `out_ts`.
+    /// With `sparse` only the neurons with a synapse from a spiking neuron are updated.
+    pub(crate) fn step_layer(
+        layer: &mut Layer<M>,
+        layer_idx: usize,
+        input_spike: &[Spike],
+        ts: u128,
+        out_ts: u128,
+        is_last: bool,
+        sparse: bool,
+    ) -> Vec<Spike> {
+        // inject the faults of the fault list active from this time
+        layer.activate_faults(ts);
+        layer.apply_drift(ts);
+
+        // only the rows of the spiking neurons are read to find the neurons to update
+        let neurons: Vec<usize> = if sparse {
+            let dt = out_ts - ts;
+            Self::targets(input_spike, &layer.input_weights, layer, |spike, neuron_idx| {
+                layer.receives(spike, neuron_idx, ts, dt)
+            })
+        } else {
+            (0..layer.num_neurons()).collect()
+        };
+        let mut layer_output = vec![];
+        for neuron_idx in neurons {
+            // with delays a neuron receives only the spikes of the synapses with the elapsed delay
+            let received: Cow<[Spike]> = match layer.delays {
+                Some(_) => input_spike
+                    .iter()
+                    .filter(|spike| layer.receives(spike, neuron_idx, ts, out_ts - ts))
+                    .cloned()
+                    .collect(),
+                None => Cow::Borrowed(input_spike),
+            };
+            //do the sum considering the possible errors
+            let sum = Self::weighted_sum(&received, layer, neuron_idx);
+            let fired = M::handle_spike(layer.get_neuron_mut(neuron_idx).unwrap(), sum, ts) == 1.0;
+            if fired {
+                layer_output.push(Spike::new(out_ts, layer_idx, neuron_idx));
+            }
+            if let Some(trace) = layer.probes.iter_mut().find(|trace| trace.neuron == neuron_idx) {
+                trace.sample(ts, fired, M::state(&layer.neurons[neuron_idx]));
+            }
+        }
+        if let Some(recorder) = &mut layer.recorder {
+            recorder.record(layer_idx, &layer_output);
+        }
+        if !is_last && !layer_output.is_empty() {
+            if sparse {
+                for neuron_idx in Self::targets(&layer_output, &layer.intra_weights, layer, |_, _| true) {
+                    let sum = layer.calculate_sum(layer_output.clone(), neuron_idx as u128);
+                    M::update_v_mem(layer.get_neuron_mut(neuron_idx).unwrap(), sum);
+                }
+            } else {
+                layer.update_layer_cycle(&layer_output);
+            }
+        }
+        layer_output
+    }
+
+    /// The neurons with a synapse, with a weight other than zero and accepted by `receives`, from one of the
+    /// spiking neurons, in order. Only the rows of `weights` of the spiking neurons are read.
+    fn targets(
+        spikes: &[Spike],
+        weights: &DMatrix<f64>,
+        layer: &Layer<M>,
+        receives: impl Fn(&Spike, usize) -> bool,
+    ) -> Vec<usize> {
+        let mut targets: Vec<usize> = spikes
+            .iter()
+            .flat_map(|spike| {
+                weights
+                    .row(spike.neuron_id)
+                    .iter()
+                    .enumerate()
+                    .filter(|(neuron_idx, weight)| {
+                        layer.read_weight(**weight) != 0.0 && receives(spike, *neuron_idx)
+                    })
+                    .map(|(neuron_idx, _)| neuron_idx)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+        targets
     }
 
     /// Calculates the sum of weighted inputs based on the received spikes and the layer's configuration.
@@ -554,16 +1114,20 @@ impl<M: Model + Clone> NN<M> {
     /// let sum = NN::calculate_sum(input_spike_tmp, nn.unwrap().layers.last().unwrap(), neuron_idx as u128);
     /// ```
     pub fn calculate_sum(input_spike_tmp: Vec<Spike>, layer: &Layer<M>, neuron_idx: u128) -> f64 {
-        let neuron = layer.get_neuron(neuron_idx as usize).unwrap();
-        let inputs_to_sum: Vec<f64> = input_spike_tmp
-            .into_iter()
-            .map(|spike| layer.input_weights[(spike.neuron_id, neuron_idx as usize)])
+        Self::weighted_sum(&input_spike_tmp, layer, neuron_idx as usize)
+    }
+
+    fn weighted_sum(input_spike: &[Spike], layer: &Layer<M>, neuron_idx: usize) -> f64 {
+        let neuron = layer.get_neuron(neuron_idx).unwrap();
+        let inputs_to_sum: Vec<f64> = input_spike
+            .iter()
+            .map(|spike| layer.read_weight(layer.input_weights[(spike.neuron_id, neuron_idx)]))
             .collect();
 
         if let Some(mut heap_vec) = M::get_heap(neuron) {
             heap_vec.sum_all(&inputs_to_sum)
         } else {
-            inputs_to_sum.iter().sum()
+            layer.precision.sum(&inputs_to_sum)
         }
     }
 
@@ -0,0 +1,192 @@
+//! # Simulation Output
+//!
+//! A [SimulationOutput] holds the spike train of every neuron of the output layer: the neuron index and
+//! the sorted times of its spikes. It is returned by all the simulation engines of [NN](crate::NN), and it
+//! is (de)serialized in the same JSON format of the input spikes:
+//!
+//! ```json
+//! [{"neuron": 0, "spikes": [2, 4]}, {"neuron": 1, "spikes": []}]
+//! ```
+//!
+//! The output also holds the traces of the probed neurons (see [probe](super::probe)) and the spikes recorded
+//! by the recorder of the network (see [recorder](super::recorder)), which are not part of its JSON nor of
+//! its comparison: two outputs are equal if their spike trains are equal. The traces can be serialized on
+//! their own.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::output::SimulationOutput;
+//!
+//! let output = SimulationOutput::from(vec![(0, vec![2, 4]), (1, vec![])]);
+//! assert_eq!(output.spikes(0), &[2, 4]);
+//! assert_eq!(output.total_spikes(), 2);
+//! assert_eq!(serde_json::to_string(&output).unwrap(), r#"[{"neuron":0,"spikes":[2,4]},{"neuron":1,"spikes":[]}]"#);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use super::probe::ProbeTrace;
+use super::recorder::SpikeRecorder;
+
+/// The spike trains of the output neurons of a simulation, with the traces of the probed neurons and the
+/// recorded spikes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<SpikeTrainJson>", into = "Vec<SpikeTrainJson>")]
+pub struct SimulationOutput {
+    /// Pairs of output neuron and spike times
+    pub(crate) trains: Vec<(u128, Vec<u128>)>,
+    /// Samples of the state of the probed neurons
+    pub(crate) probes: Vec<ProbeTrace>,
+    /// Spikes of the recorded layers, if the network has a recorder
+    pub(crate) recording: Option<SpikeRecorder>,
+}
+
+impl PartialEq for SimulationOutput {
+    /// Compares the spike trains only: the traces of the probes and the recorded spikes are not part of the
+    /// output of the network.
+    fn eq(&self, other: &SimulationOutput) -> bool {
+        self.trains == other.trains
+    }
+}
+
+/// A spike train in the JSON format of the input spikes.
+#[derive(Serialize, Deserialize)]
+struct SpikeTrainJson {
+    neuron: u128,
+    spikes: Vec<u128>,
+}
+
+impl SimulationOutput {
+    /// Creates the output of `num_neurons` neurons without spikes.
+    pub fn new(num_neurons: usize) -> SimulationOutput {
+        SimulationOutput {
+            trains: (0..num_neurons).map(|i| (i as u128, vec![])).collect(),
+            probes: vec![],
+            recording: None,
+        }
+    }
+
+    /// Adds a spike at time `ts` to the train of the output neuron at index `neuron_idx`.
+    pub(crate) fn push(&mut self, neuron_idx: usize, ts: u128) {
+        self.trains[neuron_idx].1.push(ts);
+    }
+
+    pub fn num_neurons(&self) -> usize {
+        self.trains.len()
+    }
+
+    /// The spike times of an output neuron, empty if the neuron does not exist.
+    pub fn spikes(&self, neuron: u128) -> &[u128] {
+        self.trains
+            .iter()
+            .find(|(n, _)| *n == neuron)
+            .map_or(&[], |(_, spikes)| spikes.as_slice())
+    }
+
+    /// Number of spikes of an output neuron.
+    pub fn spike_count(&self, neuron: u128) -> usize {
+        self.spikes(neuron).len()
+    }
+
+    /// Number of spikes of all the output neurons.
+    pub fn total_spikes(&self) -> usize {
+        self.trains.iter().map(|(_, spikes)| spikes.len()).sum()
+    }
+
+    /// True if no output neuron fired.
+    pub fn is_silent(&self) -> bool {
+        self.trains.iter().all(|(_, spikes)| spikes.is_empty())
+    }
+
+    /// Iterates over the pairs of output neuron and spike times.
+    pub fn iter(&self) -> std::slice::Iter<'_, (u128, Vec<u128>)> {
+        self.trains.iter()
+    }
+
+    /// The pairs of output neuron and spike times, in the format of the input spikes.
+    pub fn as_slice(&self) -> &[(u128, Vec<u128>)] {
+        &self.trains
+    }
+
+    pub fn into_vec(self) -> Vec<(u128, Vec<u128>)> {
+        self.trains
+    }
+
+    /// The traces of all the probed neurons, by layer.
+    pub fn probes(&self) -> &[ProbeTrace] {
+        &self.probes
+    }
+
+    /// The trace of a probed neuron, or [None] if the neuron has no probe.
+    pub fn probe(&self, layer: usize, neuron: usize) -> Option<&ProbeTrace> {
+        self.probes
+            .iter()
+            .find(|trace| trace.layer == layer && trace.neuron == neuron)
+    }
+
+    /// The spikes recorded in this simulation, or [None] if the network has no recorder.
+    pub fn recording(&self) -> Option<&SpikeRecorder> {
+        self.recording.as_ref()
+    }
+}
+
+impl From<Vec<(u128, Vec<u128>)>> for SimulationOutput {
+    fn from(trains: Vec<(u128, Vec<u128>)>) -> Self {
+        SimulationOutput {
+            trains,
+            probes: vec![],
+            recording: None,
+        }
+    }
+}
+
+impl From<SimulationOutput> for Vec<(u128, Vec<u128>)> {
+    fn from(output: SimulationOutput) -> Self {
+        output.trains
+    }
+}
+
+impl From<Vec<SpikeTrainJson>> for SimulationOutput {
+    fn from(trains: Vec<SpikeTrainJson>) -> Self {
+        SimulationOutput {
+            trains: trains.into_iter().map(|train| (train.neuron, train.spikes)).collect(),
+            probes: vec![],
+            recording: None,
+        }
+    }
+}
+
+impl From<SimulationOutput> for Vec<SpikeTrainJson> {
+    fn from(output: SimulationOutput) -> Self {
+        output
+            .trains
+            .into_iter()
+            .map(|(neuron, spikes)| SpikeTrainJson { neuron, spikes })
+            .collect()
+    }
+}
+
+impl PartialEq<Vec<(u128, Vec<u128>)>> for SimulationOutput {
+    fn eq(&self, other: &Vec<(u128, Vec<u128>)>) -> bool {
+        self.trains == *other
+    }
+}
+
+impl IntoIterator for SimulationOutput {
+    type Item = (u128, Vec<u128>);
+    type IntoIter = std::vec::IntoIter<(u128, Vec<u128>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.trains.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SimulationOutput {
+    type Item = &'a (u128, Vec<u128>);
+    type IntoIter = std::slice::Iter<'a, (u128, Vec<u128>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.trains.iter()
+    }
+}
@@ -0,0 +1,96 @@
+//! # State Probes
+//!
+//! Why did a neuron fire, or not? A probe attached to a neuron with [NN::add_probe](crate::NN::add_probe)
+//! samples its state every time it integrates its inputs: the membrane potential (`v_mem`), the time of
+//! the last update (`ts_old`) and the state specific to the neuron model (see
+//! [Model::state](crate::Model::state)). The samples are returned with the output of every simulation,
+//! as a [ProbeTrace] per probe.
+//!
+//! The samples are taken right after the update, so the membrane potential of a sample at which the
+//! neuron fired is the reset one.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use nalgebra::DMatrix;
+//! use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+//! use spiking_nn_resilience::NN;
+//!
+//! let mut nn = NN::<LeakyIntegrateFire>::new()
+//!     .layer(
+//!         vec![LifNeuron::new(0.0, 0.0, 1.5, 1.0)],
+//!         DMatrix::from_vec(1, 1, vec![1.0]),
+//!         DMatrix::from_vec(1, 1, vec![0.0]),
+//!     )
+//!     .unwrap();
+//! nn.add_probe(0, 0).unwrap();
+//!
+//! let output = nn.solve_sequential(vec![(0, vec![1, 2])]);
+//! let trace = output.probe(0, 0).unwrap();
+//! assert_eq!(trace.series("v_mem").unwrap()[0], (1, 1.0));
+//! assert_eq!(trace.fired(), vec![false, false]);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// The state of a neuron after an update.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSample {
+    /// Time of the update
+    pub ts: u128,
+    /// True if the neuron fired at this update
+    pub fired: bool,
+    /// Values of the state variables, in the order of [ProbeTrace::variables]
+    pub values: Vec<f64>,
+}
+
+/// The samples of the state of a probed neuron.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeTrace {
+    pub layer: usize,
+    pub neuron: usize,
+    /// Names of the state variables of the neuron
+    pub variables: Vec<String>,
+    pub samples: Vec<StateSample>,
+}
+
+impl ProbeTrace {
+    /// Creates the trace of a neuron without samples.
+    pub fn new(layer: usize, neuron: usize) -> ProbeTrace {
+        ProbeTrace {
+            layer,
+            neuron,
+            variables: vec![],
+            samples: vec![],
+        }
+    }
+
+    /// Adds a sample of the state of the neuron.
+    pub(crate) fn sample(&mut self, ts: u128, fired: bool, state: Vec<(&'static str, f64)>) {
+        if self.variables.is_empty() {
+            self.variables = state.iter().map(|(name, _)| name.to_string()).collect();
+        }
+        self.samples.push(StateSample {
+            ts,
+            fired,
+            values: state.into_iter().map(|(_, val)| val).collect(),
+        });
+    }
+
+    /// The time series of a state variable, as pairs of time and value, or [None] if the neuron does not
+    /// have the variable.
+    pub fn series(&self, variable: &str) -> Option<Vec<(u128, f64)>> {
+        let idx = self.variables.iter().position(|name| name == variable)?;
+        Some(
+            self.samples
+                .iter()
+                .map(|sample| (sample.ts, sample.values[idx]))
+                .collect(),
+        )
+    }
+
+    /// For every sample, true if the neuron fired.
+    pub fn fired(&self) -> Vec<bool> {
+        self.samples.iter().map(|sample| sample.fired).collect()
+    }
+}
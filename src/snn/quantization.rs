@@ -0,0 +1,349 @@
+//! # Quantization
+//!
+//! Before a network trained in `f64` can run on integer hardware its weights and parameters must be
+//! quantized. This module converts a network of [LeakyIntegrateFire] neurons to integers of a chosen bit
+//! width, with a scale factor for every layer:
+//!
+//! - the input and intra-layer weights of a layer share one scale factor;
+//! - the potentials of the neurons of a layer (`v_rest`, `v_reset` and `v_th`) share another one;
+//! - the time constants of the neurons of a layer have their own.
+//!
+//! The quantization is either symmetric (`x = q * scale`) or asymmetric (`x = (q - zero_point) * scale`).
+//! The quantized network is not an integer network: it is simulated in `f64` with the dequantized values
+//! (fake quantization), so the rounding of the weights and parameters is modelled, but not the one of the
+//! membrane potential and of the other intermediate results. Its output can be compared with the one of
+//! the original network before any fault injection.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::quantization::{QuantParams, QuantizationScheme};
+//!
+//! let params = QuantParams::from_range(-1.0, 1.0, 8, QuantizationScheme::Symmetric).unwrap();
+//! assert_eq!(params.quantize(1.0), 127);
+//! assert_eq!(params.quantize(-1.0), -127);
+//! assert_eq!(params.quantize(0.25), 32);
+//! ```
+
+use std::fmt::{self, Display};
+
+use nalgebra::DMatrix;
+
+use crate::snn::model::lif::{LeakyIntegrateFire, LifNeuron};
+use crate::snn::output::SimulationOutput;
+use crate::NN;
+
+/// How the real values are mapped on the integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuantizationScheme {
+    /// Signed integers centered on zero: `x = q * scale`
+    Symmetric,
+    /// Unsigned integers with an offset: `x = (q - zero_point) * scale`
+    Asymmetric,
+}
+
+/// Parameters mapping a set of real values on integers of `bits` bits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantParams {
+    pub scale: f64,
+    pub zero_point: i64,
+    pub bits: u32,
+    pub scheme: QuantizationScheme,
+}
+
+/// A layer of a quantized network: the integer weights and the parameters used to obtain them.
+#[derive(Debug, Clone)]
+pub struct QuantizedLayer {
+    pub(crate) input_weights: DMatrix<i64>,
+    pub(crate) intra_weights: DMatrix<i64>,
+    pub(crate) weights: QuantParams,
+    pub(crate) potentials: QuantParams,
+    pub(crate) tau: QuantParams,
+}
+
+/// A quantized network, together with the network of the dequantized values used to simulate it.
+#[derive(Clone)]
+pub struct QuantizedNN {
+    pub(crate) layers: Vec<QuantizedLayer>,
+    pub(crate) nn: NN<LeakyIntegrateFire>,
+}
+
+/// Divergence between the output spike trains of a network and of its quantized version.
+#[derive(Debug, Clone)]
+pub struct QuantizationReport {
+    /// Output of the original network
+    pub float_output: SimulationOutput,
+    /// Output of the quantized network
+    pub quantized_output: SimulationOutput,
+    /// Spikes of the original network missing in the quantized one
+    pub missing_spikes: usize,
+    /// Spikes of the quantized network missing in the original one
+    pub extra_spikes: usize,
+    /// Number of output neurons whose spike train differs
+    pub diverging_neurons: usize,
+}
+
+impl QuantParams {
+    /// Computes the parameters mapping the interval `[min, max]` on integers of `bits` bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The smallest value to represent.
+    /// * `max` - The largest value to represent.
+    /// * `bits` - The width of the integers, between 2 and 32.
+    /// * `scheme` - Symmetric or asymmetric quantization.
+    ///
+    /// # Returns
+    ///
+    /// The parameters, or an error if the width is not supported or the interval is not finite.
+    pub fn from_range(min: f64, max: f64, bits: u32, scheme: QuantizationScheme) -> Result<QuantParams, String> {
+        if !(2..=32).contains(&bits) {
+            return Err(format!("Invalid quantization width: {} bits", bits));
+        }
+        if !min.is_finite() || !max.is_finite() || min > max {
+            return Err(format!("Invalid quantization range: [{}, {}]", min, max));
+        }
+        // zero must be exactly representable, so that the missing connections stay missing
+        let (min, max) = (min.min(0.0), max.max(0.0));
+        let (scale, zero_point) = match scheme {
+            QuantizationScheme::Symmetric => (min.abs().max(max) / ((1i64 << (bits - 1)) - 1) as f64, 0),
+            QuantizationScheme::Asymmetric => {
+                let scale = (max - min) / ((1i64 << bits) - 1) as f64;
+                (scale, if scale > 0.0 { (-min / scale).round() as i64 } else { 0 })
+            }
+        };
+        Ok(QuantParams {
+            // a set of zeros is represented with any scale
+            scale: if scale > 0.0 { scale } else { 1.0 },
+            zero_point,
+            bits,
+            scheme,
+        })
+    }
+
+    /// Smallest representable integer.
+    pub fn min_int(&self) -> i64 {
+        match self.scheme {
+            QuantizationScheme::Symmetric => -((1i64 << (self.bits - 1)) - 1),
+            QuantizationScheme::Asymmetric => 0,
+        }
+    }
+
+    /// Largest representable integer.
+    pub fn max_int(&self) -> i64 {
+        match self.scheme {
+            QuantizationScheme::Symmetric => (1i64 << (self.bits - 1)) - 1,
+            QuantizationScheme::Asymmetric => (1i64 << self.bits) - 1,
+        }
+    }
+
+    /// Converts a real value to the nearest integer, saturating outside the range.
+    pub fn quantize(&self, val: f64) -> i64 {
+        ((val / self.scale).round() as i64 + self.zero_point).clamp(self.min_int(), self.max_int())
+    }
+
+    /// Converts an integer back to its real value.
+    pub fn dequantize(&self, q: i64) -> f64 {
+        (q - self.zero_point) as f64 * self.scale
+    }
+
+    /// Returns the nearest real value representable with these parameters.
+    pub fn round(&self, val: f64) -> f64 {
+        self.dequantize(self.quantize(val))
+    }
+}
+
+impl QuantizedLayer {
+    pub fn input_weights(&self) -> &DMatrix<i64> {
+        &self.input_weights
+    }
+
+    pub fn intra_weights(&self) -> &DMatrix<i64> {
+        &self.intra_weights
+    }
+
+    /// Parameters of the input and intra-layer weights.
+    pub fn weights(&self) -> QuantParams {
+        self.weights
+    }
+
+    /// Parameters of `v_rest`, `v_reset` and `v_th`.
+    pub fn potentials(&self) -> QuantParams {
+        self.potentials
+    }
+
+    /// Parameters of the time constants.
+    pub fn tau(&self) -> QuantParams {
+        self.tau
+    }
+}
+
+impl QuantizedNN {
+    /// Quantizes all the weights and the parameters of the neurons of a network.
+    ///
+    /// # Arguments
+    ///
+    /// * `nn` - The network to quantize.
+    /// * `bits` - The width of the integers, between 2 and 32.
+    /// * `scheme` - Symmetric or asymmetric quantization.
+    ///
+    /// # Returns
+    ///
+    /// The quantized network, or an error if the width is not supported or a value is not finite.
+    pub fn new(nn: &NN<LeakyIntegrateFire>, bits: u32, scheme: QuantizationScheme) -> Result<QuantizedNN, String> {
+        let mut quantized = nn.clone();
+        let mut layers = Vec::with_capacity(nn.layers.len());
+
+        for layer in quantized.layers.iter_mut() {
+            let weights = params_of(
+                layer.input_weights.iter().chain(layer.intra_weights.iter()).copied(),
+                bits,
+                scheme,
+            )?;
+            let potentials = params_of(
+                layer.neurons.iter().flat_map(|n| [n.v_rest, n.v_reset, n.v_th]),
+                bits,
+                scheme,
+            )?;
+            let tau = params_of(layer.neurons.iter().map(|n| n.tau), bits, scheme)?;
+
+            let input_weights = layer.input_weights.map(|w| weights.quantize(w));
+            let intra_weights = layer.intra_weights.map(|w| weights.quantize(w));
+            layer.input_weights = input_weights.map(|q| weights.dequantize(q));
+            layer.intra_weights = intra_weights.map(|q| weights.dequantize(q));
+            for neuron in layer.neurons.iter_mut() {
+                quantize_neuron(neuron, &potentials, &tau);
+            }
+
+            layers.push(QuantizedLayer {
+                input_weights,
+                intra_weights,
+                weights,
+                potentials,
+                tau,
+            });
+        }
+
+        Ok(QuantizedNN {
+            layers,
+            nn: quantized,
+        })
+    }
+
+    pub fn get_layer(&self, layer: usize) -> Option<&QuantizedLayer> {
+        self.layers.get(layer)
+    }
+
+    /// The network of the dequantized values, ready to be simulated or tested with [crate::snn::resilience::Resilience].
+    pub fn network(&self) -> &NN<LeakyIntegrateFire> {
+        &self.nn
+    }
+
+    pub fn into_network(self) -> NN<LeakyIntegrateFire> {
+        self.nn
+    }
+
+    /// Simulates both the original and the quantized network with the same input, and compares their outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `original` - The network this one was quantized from.
+    /// * `input` - The input spikes, as pairs of input neuron and spike times.
+    ///
+    /// # Returns
+    ///
+    /// A [QuantizationReport] with the divergence of the output spike trains.
+    pub fn compare(&self, original: &NN<LeakyIntegrateFire>, input: Vec<(u128, Vec<u128>)>) -> QuantizationReport {
+        let float_output = original.solve_threaded(input.clone());
+        let quantized_output = self.nn.solve_threaded(input);
+        QuantizationReport::new(float_output, quantized_output)
+    }
+}
+
+impl QuantizationReport {
+    /// Compares two outputs, spike by spike. Two spikes of a neuron at the same time count twice.
+    pub fn new(float_output: SimulationOutput, quantized_output: SimulationOutput) -> QuantizationReport {
+        let neurons: Vec<u128> = float_output
+            .iter()
+            .chain(quantized_output.iter())
+            .map(|(neuron, _)| *neuron)
+            .collect();
+        let (mut missing_spikes, mut extra_spikes, mut diverging) = (0, 0, vec![]);
+        for neuron in neurons {
+            let (float_train, quantized_train) = (float_output.spikes(neuron), quantized_output.spikes(neuron));
+            if float_train != quantized_train && !diverging.contains(&neuron) {
+                missing_spikes += unmatched(float_train, quantized_train);
+                extra_spikes += unmatched(quantized_train, float_train);
+                diverging.push(neuron);
+            }
+        }
+
+        QuantizationReport {
+            missing_spikes,
+            extra_spikes,
+            diverging_neurons: diverging.len(),
+            float_output,
+            quantized_output,
+        }
+    }
+
+    /// True if the quantized network produces exactly the same spike trains of the original one.
+    pub fn is_equivalent(&self) -> bool {
+        self.float_output == self.quantized_output
+    }
+
+    /// Fraction of the spikes of the two outputs that are not in both, between 0 (same output) and 1.
+    pub fn divergence(&self) -> f64 {
+        let total = self.float_output.total_spikes() + self.quantized_output.total_spikes();
+        if total == 0 {
+            0.0
+        } else {
+            (self.missing_spikes + self.extra_spikes) as f64 / total as f64
+        }
+    }
+}
+
+impl Display for QuantizationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Missing spikes: {}", self.missing_spikes)?;
+        writeln!(f, "Extra spikes: {}", self.extra_spikes)?;
+        writeln!(f, "Diverging output neurons: {}", self.diverging_neurons)?;
+        write!(f, "Divergence: {:.2}%", self.divergence() * 100.0)
+    }
+}
+
+fn params_of(
+    values: impl Iterator<Item = f64>,
+    bits: u32,
+    scheme: QuantizationScheme,
+) -> Result<QuantParams, String> {
+    // the range always holds zero, so that a missing synapse stays missing
+    let mut range = (0.0f64, 0.0f64);
+    for v in values {
+        if !v.is_finite() {
+            return Err(format!("Cannot quantize the value {}", v));
+        }
+        range = (range.0.min(v), range.1.max(v));
+    }
+    QuantParams::from_range(range.0, range.1, bits, scheme)
+}
+
+fn quantize_neuron(neuron: &mut LifNeuron, potentials: &QuantParams, tau: &QuantParams) {
+    neuron.v_rest = potentials.round(neuron.v_rest);
+    neuron.v_reset = potentials.round(neuron.v_reset);
+    neuron.v_th = potentials.round(neuron.v_th);
+    neuron.tau = tau.round(neuron.tau);
+}
+
+/// Number of spikes of a sorted train without a spike at the same time in the other sorted train.
+fn unmatched(train: &[u128], other: &[u128]) -> usize {
+    let mut other = other.iter().peekable();
+    let mut count = 0;
+    for ts in train {
+        while other.next_if(|other_ts| *other_ts < ts).is_some() {}
+        if other.next_if_eq(&ts).is_none() {
+            count += 1;
+        }
+    }
+    count
+}
@@ -0,0 +1,109 @@
+//! # Spike Recorder
+//!
+//! Only the spikes of the last layer reach the output of a simulation. A [SpikeRecorder] attached to a
+//! network with [NN::use_recorder](crate::NN::use_recorder) also captures the spikes of the hidden layers,
+//! of all of them or of some of them, to debug the behaviour of a network or to follow a fault while it
+//! propagates from layer to layer.
+//!
+//! Every simulation records into its own copy of the recorder, returned with the output of the simulation
+//! by [SimulationOutput::recording], as the traces of the probes. So the faulty clones of a network in a
+//! resilience test and the inputs of a batch never mix their spikes, and nothing grows between two
+//! simulations.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::recorder::SpikeRecorder;
+//!
+//! // only the first two layers
+//! let recorder = SpikeRecorder::with_layers(vec![0, 1]);
+//! assert!(recorder.records(1) && !recorder.records(2));
+//! assert!(recorder.spikes().is_empty());
+//! ```
+
+use super::output::SimulationOutput;
+use super::Spike;
+
+/// Recorder of the spikes fired by the layers of a network.
+#[derive(Debug, Clone, Default)]
+pub struct SpikeRecorder {
+    /// Recorded layers, [None] for all of them
+    pub(crate) layers: Option<Vec<usize>>,
+    /// Number of neurons of every layer of the network
+    pub(crate) layer_sizes: Vec<usize>,
+    /// Recorded spikes, in the order they are fired
+    pub(crate) spikes: Vec<Spike>,
+}
+
+impl SpikeRecorder {
+    /// Creates a recorder of all the layers.
+    pub fn new() -> SpikeRecorder {
+        SpikeRecorder::default()
+    }
+
+    /// Creates a recorder of the given layers only.
+    pub fn with_layers(layers: Vec<usize>) -> SpikeRecorder {
+        SpikeRecorder {
+            layers: Some(layers),
+            ..SpikeRecorder::default()
+        }
+    }
+
+    /// True if the spikes of the layer are recorded.
+    pub fn records(&self, layer: usize) -> bool {
+        self.layers.as_ref().is_none_or(|layers| layers.contains(&layer))
+    }
+
+    /// Records the spikes fired by a layer, if it is recorded.
+    pub(crate) fn record(&mut self, layer: usize, spikes: &[Spike]) {
+        if self.records(layer) {
+            self.spikes.extend_from_slice(spikes);
+        }
+    }
+
+    /// The spikes recorded since the last call, leaving the recorder in place.
+    pub(crate) fn take(&mut self) -> SpikeRecorder {
+        SpikeRecorder {
+            layers: self.layers.clone(),
+            layer_sizes: self.layer_sizes.clone(),
+            spikes: std::mem::take(&mut self.spikes),
+        }
+    }
+
+    /// Joins the spikes recorded by the layers of a network, [None] if the network has no recorder.
+    pub(crate) fn merge<I: IntoIterator<Item = SpikeRecorder>>(recorders: I) -> Option<SpikeRecorder> {
+        recorders.into_iter().reduce(|mut merged, recorder| {
+            merged.spikes.extend(recorder.spikes);
+            merged
+        })
+    }
+
+    /// All the recorded spikes, sorted by time, layer and neuron. The time of a spike is the one it
+    /// reaches the next layer, as in the output of the simulation.
+    pub fn spikes(&self) -> Vec<Spike> {
+        let mut spikes = self.spikes.clone();
+        spikes.sort();
+        spikes
+    }
+
+    /// The spike trains of all the neurons of a layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The index of the layer.
+    ///
+    /// # Returns
+    ///
+    /// The spike trains, or [None] if the layer is not recorded or not in the network of the recorder.
+    pub fn layer(&self, layer: usize) -> Option<SimulationOutput> {
+        let num_neurons = *self.layer_sizes.get(layer)?;
+        if !self.records(layer) {
+            return None;
+        }
+        let mut trains = SimulationOutput::new(num_neurons);
+        for spike in self.spikes().into_iter().filter(|spike| spike.layer_id == layer) {
+            trains.push(spike.neuron_id, spike.ts);
+        }
+        Some(trains)
+    }
+}
@@ -20,7 +20,13 @@
 //! ## Resilience Testing
 //!
 //! To perform resilience testing, users can create an instance of the `Resilience` struct and execute the test on a given SNN.
-//! The library supports various fault types, including faults in neurons, membrane potentials, and specific components like full adders and comparators.
+//! The library supports various fault types, including faults in neurons, weights, membrane potentials, and specific components like full adders,
+//! comparators, the reset logic, and the multiplier and exponential unit computing the leak of the membrane potential.
+//! Neuron parameters and weights can optionally be stored in ECC protected words (see `with_ecc`): the report then
+//! counts how many faults were corrected, detected or left uncorrected.
+//! The input encoding can be tested too, with the "input" component (or "timestamp", "dropped event",
+//! "stuck on" and "stuck off"): the fault then corrupts the input spikes instead of the network. Input faults
+//! are drawn with uniform sampling only, and are not part of the comparisons of durations or networks.
 //!
 //! ```rust
 //! use spiking_nn_resilience::{NN, LeakyIntegrateFire, Resilience, are_equal};
@@ -53,13 +59,24 @@
 //! }
 //! ```
 
-use crate::snn::model::lif::*;
-use crate::NN;
-use rand::Rng;
+use crate::{Model, NN};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use crate::snn::Spike;
 
+use super::comparison::{check_topology, ComparedFault, ComparisonReport};
+use super::dataset::{classify, Dataset, DatasetReport, FaultSweep, SweepFault};
+use super::fault::{Fault, FaultDuration};
+use super::input_fault::{InputComponent, InputFault};
+use super::sampling::{Allocation, Estimate, SamplePlan, Sampling};
+use super::mitigation::{MitigationStats, RangeCheckConfig};
+use super::model::ecc::{EccOutcome, EccStats};
 use super::model::Stuck;
+use super::nn::SimulationMode;
+use super::output::SimulationOutput;
 
 /// The struct that contains the input configuration
 #[derive(Clone)]
@@ -70,6 +87,16 @@ pub struct Resilience {
     pub(crate) stuck_type: Stuck,
     /// The number of times to do the resilience test
     pub(crate) times: u128,
+    /// Number of faulty bits in the ECC codeword of a parameter or weight, [None] if ECC is disabled
+    pub(crate) ecc: Option<usize>,
+    /// Range check mitigation to compare with the unprotected network, if any
+    pub(crate) range_check: Option<RangeCheckConfig>,
+    /// Explicit list of faults to inject instead of the random ones, if any
+    pub(crate) faults: Option<Vec<Fault>>,
+    /// How the random faults are chosen
+    pub(crate) sampling: Sampling,
+    /// Engine simulating the fault free and the faulty networks
+    pub(crate) simulation: SimulationMode,
 }
 
 impl Resilience {
@@ -89,6 +116,11 @@ impl Resilience {
             components,
             stuck_type,
             times,
+            ecc: None,
+            range_check: None,
+            faults: None,
+            sampling: Sampling::Uniform,
+            simulation: SimulationMode::default(),
         };
     }
 
@@ -96,109 +128,814 @@ impl Resilience {
         return self.components[rand::thread_rng().gen_range(0..self.components.len())].clone();
     }
 
-    /// Creates a new `Resilience` instance with the given components, stuck type, and test repetition count.
+    /// Enables the ECC protection of the neuron parameters and of the weights.
+    ///
+    /// Every random fault injected in the "neurons" or "weights" components hits `num_bits` random bits
+    /// of the SEC-DED codeword storing the value in its precision or fixed point format: with 1 bit the
+    /// error is always corrected, with 2 bits it is detected, with more bits it may reach the network.
+    /// The faults of a fault list or of a sampling plan hit a given bit of the value, and are not
+    /// injected through the ECC.
+    pub fn with_ecc(mut self, num_bits: usize) -> Resilience {
+        self.ecc = Some(num_bits);
+        self
+    }
+
+    /// Enables the comparison with the range check mitigation.
+    ///
+    /// Every faulty network is simulated twice: as it is and with the range check enabled, so the report
+    /// shows how many of the wrong outputs the mitigation avoids and how often it triggered.
+    pub fn with_range_check(mut self, range_check: RangeCheckConfig) -> Resilience {
+        self.range_check = Some(range_check);
+        self
+    }
+
+    /// Replaces the random faults with an explicit fault list.
+    ///
+    /// Every fault of the list is injected in its own faulty run, at its time, and the components, stuck
+    /// type and number of repetitions of this configuration are ignored. Faults that do not exist in the
+    /// tested network are skipped.
+    pub fn with_faults(mut self, faults: Vec<Fault>) -> Resilience {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Chooses the random faults with stratified or importance sampling instead of uniform sampling.
+    ///
+    /// The report then estimates the failure rate over the whole fault space of the components, with
+    /// the outcomes of the faults weighted by the sampling. The input encoding has no fault space: with one
+    /// of its components the test falls back to uniform sampling.
+    pub fn with_sampling(mut self, sampling: Sampling) -> Resilience {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Chooses the engine simulating the networks, sequential by default.
+    ///
+    /// Both engines give the same outputs: the threaded one spawns a thread per layer for every run, the
+    /// sequential one runs every layer in the calling thread and is faster on small networks.
+    pub fn with_simulation(mut self, simulation: SimulationMode) -> Resilience {
+        self.simulation = simulation;
+        self
+    }
+
+    /// Executes the resilience test on the given SNN and prints a summary of the results.
     ///
     /// # Arguments
     ///
-    /// * `components` - List of all components to be tested for resilience.
-    /// * `stuck_type` - Stuck type: 0 for stuck_at_0, 1 for stuck_at_1, 2 for transient_bit.
-    /// * `times` - The number of times to perform the resilience test.
+    /// * `snn` - The spiking neural network to test.
+    /// * `input` - Vector of tuples (neuron_id, vector of spikes) given as input to the network.
     ///
     /// # Returns
     ///
-    /// * `Resilience` - A new `Resilience` instance.
-    pub fn execute_resilience_test(
+    /// * `Arc<Mutex<Vec<(u128, Vec<u128>)>>>` - The output of the network without faults.
+    pub fn execute_resilience_test<M: Model + Clone>(
         &self,
-        snn: NN<LeakyIntegrateFire>,
+        snn: NN<M>,
         input: Vec<(u128, Vec<u128>)>,
     ) -> Arc<Mutex<Vec<(u128, Vec<u128>)>>> {
-
-        let mut count_right_outputs: u64 = 0;
-        let time_init = std::time::Instant::now();
-        let right_output = snn.clone().solve_multiple_vec_spike(input.clone());
-        // println!("{:?}", right_output);
         println!("Executing resilience test for given Spiking Neural Network. Total number of input spikes: {:?}", Spike::vec_of_all_spikes(input.clone()));
         println!("Total number of input spikes: {}", Spike::vec_of_all_spikes(input.clone()).len());
-        println!("Total number of output spikes: {}", Spike::vec_of_all_spikes(right_output.lock().unwrap().clone()).len());
-        for _ in 0..self.times {
+
+        let report = self.run_resilience_test(snn, input);
+
+        println!("Total number of output spikes: {}", Spike::vec_of_all_spikes(report.right_output.clone()).len());
+        println!("{}", report);
+        Arc::new(Mutex::new(report.right_output))
+    }
+
+    /// Executes the resilience test on the given SNN without printing anything.
+    ///
+    /// At every repetition a fault is injected in a random component, chosen between the ones of this
+    /// configuration, and the output of the faulty network is compared with the fault free one.
+    ///
+    /// # Arguments
+    ///
+    /// * `snn` - The spiking neural network to test.
+    /// * `input` - Vector of tuples (neuron_id, vector of spikes) given as input to the network.
+    ///
+    /// # Returns
+    ///
+    /// * `ResilienceReport` - The outcomes of the test.
+    pub fn run_resilience_test<M: Model + Clone>(
+        &self,
+        snn: NN<M>,
+        input: Vec<(u128, Vec<u128>)>,
+    ) -> ResilienceReport {
+        let time_init = std::time::Instant::now();
+        let right_output = snn.solve(input.clone(), self.simulation);
+        let mut report = ResilienceReport {
+            components: self.components.clone(),
+            stuck_type: self.stuck_type.clone(),
+            right_output: right_output.as_slice().to_vec(),
+            times: self.times,
+            right_outputs: 0,
+            ecc: self.ecc.map(|_| EccStats::default()),
+            mitigated_right_outputs: self.range_check.as_ref().map(|_| 0),
+            mitigation: self.range_check.as_ref().map(|_| MitigationStats::default()),
+            fault_outcomes: None,
+            sampling: None,
+            estimate: Estimate::binomial(0, 0),
+            duration: Default::default(),
+        };
+        let range_check = self
+            .range_check
+            .as_ref()
+            .map(|config| config.build(&snn, input.clone()));
+
+        let (plan, faults) = self.planned_faults(&snn);
+        report.sampling = match (&self.faults, &plan) {
+            (Some(_), _) => None,
+            (None, Some(_)) => Some(self.sampling.clone()),
+            (None, None) => Some(Sampling::Uniform),
+        };
+
+        if let Some(faults) = &faults {
+            report.times = faults.len() as u128;
+            report.fault_outcomes = Some(vec![]);
+        }
+
+        for trial in 0..report.times as usize {
             let mut snn_tmp = snn.clone();
-            //println!(solution);
-            //println!("Type: {}",&self.get_rand_component().to_lowercase() as &str);
-
-            //select a random component between the one chosen by the user
-            let component = self.get_rand_component().to_lowercase();
-
-            match &component as &str {
-                "neurons" | "n" | "neu" | "neuron" | "neuroni" | "neurone" => {
-                    // println!("chose neuron");
-                    let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
-                    let rand_neuron_idx = rand::thread_rng()
-                        .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
-                    snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
-                        self.stuck_type.clone(),
-                        rand_neuron_idx,
-                        vec!["v_rest", "v_reset", "v_tau", "v_th"]
-                            .get(rand::thread_rng().gen_range(0..4))
-                            .unwrap()
-                            .to_string(),
-                    );
+            let mut input = input.clone();
+
+            match &faults {
+                Some(faults) => {
+                    // the fault was already checked
+                    let _ = snn_tmp.schedule_fault(faults[trial].clone());
                 }
-                "vmem" | "potenziale di membrana" | "membrane potential" | "membrane" | "membrana" | "v_mem"=> {
-                    // println!("chose vmem");
-                    let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
-                    let rand_neuron_idx = rand::thread_rng()
-                        .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
-                    snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
-                        self.stuck_type.clone(),
-                        rand_neuron_idx,
-                        "v_mem".to_string(),
-                    )
+                None => {
+                    let (outcome, input_fault) = self.inject_random_fault(&mut snn_tmp, &input);
+                    report.record_ecc(outcome);
+                    if let Some(input_fault) = input_fault {
+                        // the fault was chosen in this input
+                        input = input_fault.apply(&input).unwrap_or(input);
+                    }
+                }
+            }
+
+            // run the same faulty network with the mitigation
+            if let Some(range_check) = &range_check {
+                let range_check = range_check.fresh();
+                let mut snn_mitigated = snn_tmp.clone();
+                snn_mitigated.use_range_check(&range_check);
+                let res = snn_mitigated.solve(input.clone(), self.simulation);
+                if res == right_output {
+                    report.mitigated_right_outputs = report.mitigated_right_outputs.map(|n| n + 1);
                 }
+                if let Some(stats) = report.mitigation.as_mut() {
+                    stats.add(&range_check.stats());
+                }
+            }
 
-                "fullAdder" | "full adder" | "full-adder" | "full_adder" | "adder" | "sommatore" => {
-                    // println!("chose full adder");
-                    let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
-                    let rand_neuron_idx = rand::thread_rng()
-                        .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
-                    snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
+            let res = snn_tmp.solve(input.clone(), self.simulation);
+            //println!("{:?}", res);
+            let right = res == right_output;
+            if right {
+                report.right_outputs += 1;
+            }
+            if let (Some(outcomes), Some(faults)) = (report.fault_outcomes.as_mut(), &faults) {
+                outcomes.push((faults[trial].clone(), right));
+            }
+        }
+
+        report.estimate = match (&plan, &report.fault_outcomes) {
+            (Some(plan), Some(outcomes)) => {
+                plan.estimate(&outcomes.iter().map(|(_, right)| !right).collect::<Vec<_>>())
+            }
+            _ => Estimate::binomial(report.times - report.right_outputs, report.times),
+        };
+        report.duration = std::time::Instant::now() - time_init;
+        report
+    }
+
+    /// Executes the resilience test over all the inputs of a dataset and prints a summary of the results.
+    ///
+    /// # Arguments
+    ///
+    /// * `snn` - The spiking neural network to test.
+    /// * `dataset` - The inputs given to the network.
+    ///
+    /// # Returns
+    ///
+    /// * `DatasetReport` - The outcomes of the test.
+    pub fn execute_dataset_test<M: Model + Clone>(&self, snn: NN<M>, dataset: &Dataset) -> DatasetReport {
+        println!("Executing resilience test for given Spiking Neural Network over {} inputs.", dataset.len());
+
+        let report = self.run_dataset_test(snn, dataset);
+
+        println!("{}", report);
+        report
+    }
+
+    /// Executes the resilience test over all the inputs of a dataset without printing anything.
+    ///
+    /// The faults are the valid faults of the fault list, or faults sampled with the sampling of this
+    /// configuration, uniformly over the fault space for uniform sampling. A component of the input
+    /// encoding is chosen as often as the other components, and gets a random fault of the spikes of the
+    /// first input. Every fault is evaluated on every input: the report gives, for every fault, the inputs
+    /// whose class (the output neuron with the most spikes) differs from the one of the fault free network.
+    /// ECC and range check are not applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `snn` - The spiking neural network to test.
+    /// * `dataset` - The inputs given to the network.
+    ///
+    /// # Returns
+    ///
+    /// * `DatasetReport` - The outcomes of the test.
+    pub fn run_dataset_test<M: Model + Clone>(&self, snn: NN<M>, dataset: &Dataset) -> DatasetReport {
+        let time_init = std::time::Instant::now();
+        let right_classes: Vec<Option<u128>> = dataset
+            .inputs()
+            .iter()
+            .map(|input| classify(snn.solve(input.clone(), self.simulation).as_slice()))
+            .collect();
+
+        // the trials of the input encoding, chosen between the components as the other faults
+        let input_trials = match self.faults {
+            Some(_) => 0,
+            None => (0..self.times)
+                .filter(|_| InputComponent::from_name(&self.get_rand_component()).is_some())
+                .count(),
+        };
+        let network = Resilience {
+            components: self
+                .components
+                .iter()
+                .filter(|name| InputComponent::from_name(name).is_none())
+                .cloned()
+                .collect(),
+            times: self.times - input_trials as u128,
+            ..self.clone()
+        };
+        let faults = match (&self.faults, network.components.is_empty() || network.times == 0) {
+            (None, true) => vec![],
+            _ => network.fault_locations(&snn, &mut rand::thread_rng()),
+        };
+        let first_input = dataset.inputs().first().cloned().unwrap_or_default();
+        let mut sweeps = Vec::with_capacity(faults.len() + input_trials);
+
+        // an input fault is chosen on the first input, skipped if it has no spike to hit
+        let trials = faults.into_iter().map(SweepFault::Network).chain(
+            (0..input_trials).filter_map(|_| self.random_input_fault(&first_input).map(SweepFault::Input)),
+        );
+        for fault in trials {
+            let mut snn_tmp = snn.clone();
+            let input_fault = match &fault {
+                // the fault was already checked
+                SweepFault::Network(fault) => {
+                    let _ = snn_tmp.schedule_fault(fault.clone());
+                    None
+                }
+                SweepFault::Input(input_fault) => Some(input_fault),
+            };
+
+            // the same faulty network, with the same fault, for every input
+            let misclassified = dataset
+                .inputs()
+                .iter()
+                .zip(right_classes.iter())
+                .enumerate()
+                .filter(|(_, (input, right_class))| {
+                    // an input fault is applied to every input that has its spike
+                    let input = match &input_fault {
+                        Some(input_fault) => input_fault.apply(input).unwrap_or((*input).clone()),
+                        None => (*input).clone(),
+                    };
+                    let res = snn_tmp.solve(input, self.simulation);
+                    let class = classify(res.as_slice());
+                    class != **right_class
+                })
+                .map(|(i, _)| i)
+                .collect();
+            sweeps.push(FaultSweep { fault, misclassified });
+        }
+
+        DatasetReport {
+            inputs: dataset.names().to_vec(),
+            right_classes,
+            faults: sweeps,
+            duration: std::time::Instant::now() - time_init,
+        }
+    }
+
+    /// Compares permanent faults and transient faults lasting a single time step in the same locations.
+    ///
+    /// The locations (layer, neuron, component and bit) are the valid faults of the fault list or the
+    /// faults sampled with the sampling of this configuration, uniformly over the fault space for uniform
+    /// sampling. Every location is injected twice with the stuck type of the fault: as a permanent fault,
+    /// from its time, and as a transient fault at its time, or at a random time of the input spikes if
+    /// the time is 0. The input encoding has no locations: with one of its components the error is printed
+    /// and there are no outcomes.
+    ///
+    /// # Arguments
+    ///
+    /// * `snn` - The spiking neural network to test.
+    /// * `input` - Vector of tuples (neuron_id, vector of spikes) given as input to the network.
+    ///
+    /// # Returns
+    ///
+    /// * `DurationReport` - The paired outcomes of every location.
+    pub fn run_duration_comparison<M: Model + Clone>(
+        &self,
+        snn: NN<M>,
+        input: Vec<(u128, Vec<u128>)>,
+    ) -> DurationReport {
+        let time_init = std::time::Instant::now();
+        let right_output = snn.solve(input.clone(), self.simulation);
+        let input_times: Vec<u128> = input.iter().flat_map(|(_, spikes)| spikes.iter().copied()).collect();
+
+        let locations = self.fault_locations(&snn, &mut rand::thread_rng());
+
+        let failed = |fault: &Fault| {
+            let mut snn_tmp = snn.clone();
+            // the fault was already checked
+            let _ = snn_tmp.schedule_fault(fault.clone());
+            snn_tmp.solve(input.clone(), self.simulation) != right_output
+        };
+        let outcomes = locations
+            .into_iter()
+            .map(|fault| {
+                let permanent = fault.clone().lasting(FaultDuration::Permanent);
+                let time = match (fault.time, input_times.is_empty()) {
+                    (0, false) => input_times[rand::thread_rng().gen_range(0..input_times.len())],
+                    (time, _) => time,
+                };
+                let transient = fault.at(time).lasting(FaultDuration::Transient);
+                PairedOutcome {
+                    permanent_failed: failed(&permanent),
+                    transient_failed: failed(&transient),
+                    permanent,
+                    transient,
+                }
+            })
+            .collect();
+
+        DurationReport {
+            right_output: right_output.into_vec(),
+            outcomes,
+            duration: std::time::Instant::now() - time_init,
+        }
+    }
+
+    /// Runs the same fault campaign on two networks with the same topology and compares their outcomes.
+    ///
+    /// The faults are the valid faults of the fault list, or faults sampled once with the sampling of this
+    /// configuration (uniformly over the fault space for uniform sampling) from a random number generator
+    /// seeded with `seed`, so the same seed always gives the same faults. Faults that do not exist in both
+    /// networks are skipped. ECC and range check are not applied, and the input encoding cannot be tested.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first network, e.g. the current one.
+    /// * `b` - The second network, e.g. the retrained or re-quantized one.
+    /// * `input` - Vector of tuples (neuron_id, vector of spikes) given as input to the networks.
+    /// * `seed` - The seed of the random faults.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ComparisonReport, String>` - The paired outcomes, or an error if the topologies differ or a
+    ///   component is part of the input encoding.
+    pub fn compare<M: Model + Clone>(
+        &self,
+        a: NN<M>,
+        b: NN<M>,
+        input: Vec<(u128, Vec<u128>)>,
+        seed: u64,
+    ) -> Result<ComparisonReport, String> {
+        check_topology(&a, &b)?;
+        if self.faults.is_none() {
+            if let Some(name) = self.components.iter().find(|name| InputComponent::from_name(name).is_some()) {
+                return Err(format!("The input encoding ({}) is not part of the networks to compare", name));
+            }
+        }
+        let time_init = std::time::Instant::now();
+        let right_a = a.solve(input.clone(), self.simulation);
+        let right_b = b.solve(input.clone(), self.simulation);
+
+        let failed = |snn: &NN<M>, fault: &Fault, right_output: &SimulationOutput| {
+            let mut snn_tmp = snn.clone();
+            snn_tmp.schedule_fault(fault.clone())?;
+            let res = snn_tmp.solve(input.clone(), self.simulation);
+            Ok::<bool, String>(res != *right_output)
+        };
+        let outcomes = self
+            .fault_locations(&a, &mut StdRng::seed_from_u64(seed))
+            .into_iter()
+            .filter_map(|fault| match (failed(&a, &fault, &right_a), failed(&b, &fault, &right_b)) {
+                (Ok(a_failed), Ok(b_failed)) => Some(ComparedFault { fault, a_failed, b_failed }),
+                (Err(err), _) | (_, Err(err)) => {
+                    println!("Skipping the fault {}: {}", fault, err);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(ComparisonReport::new(
+            self.faults.is_none().then_some(seed),
+            outcomes,
+            std::time::Instant::now() - time_init,
+        ))
+    }
+
+    /// The valid faults of the fault list or, without a fault list, the faults sampled with the sampling of
+    /// this configuration, uniformly over the whole fault space for uniform sampling.
+    ///
+    /// The link of a heap is always set, drawn from `rng` when the fault list leaves it out, so that a fault
+    /// injected more than once always hits the same link.
+    fn fault_locations<M: Model + Clone, R: Rng>(&self, snn: &NN<M>, rng: &mut R) -> Vec<Fault> {
+        if let Some(faults) = self.valid_faults(snn) {
+            return faults
+                .into_iter()
+                .map(|fault| {
+                    let num_inputs = snn.layers[fault.layer].input_weights.nrows();
+                    match (fault.link, fault.component.num_links(num_inputs)) {
+                        (None, Some(links)) => {
+                            let link = rng.gen_range(0..links);
+                            fault.link(link)
+                        }
+                        _ => fault,
+                    }
+                })
+                .collect();
+        }
+        let sampling = match &self.sampling {
+            // a single stratum is a uniform sample of the whole fault space
+            Sampling::Uniform => Sampling::Stratified {
+                by: vec![],
+                allocation: Allocation::Proportional,
+            },
+            sampling => sampling.clone(),
+        };
+        match sampling.plan_with_rng(snn, &self.components, &self.stuck_type, self.times, rng) {
+            Ok(plan) => plan.map(|plan| plan.faults().to_vec()).unwrap_or_default(),
+            Err(err) => {
+                println!("Error: {}", err);
+                vec![]
+            }
+        }
+    }
+
+    /// The sample plan of the faults, if any, and the faults to inject: the valid faults of the fault list
+    /// or the ones of the plan. Both are [None] for uniform random faults.
+    fn planned_faults<M: Model + Clone>(&self, snn: &NN<M>) -> (Option<SamplePlan>, Option<Vec<Fault>>) {
+        let plan = match (&self.faults, &self.sampling) {
+            (None, sampling) => sampling
+                .plan(snn, &self.components, &self.stuck_type, self.times)
+                .unwrap_or_else(|err| {
+                    println!("Error: {}, using uniform sampling", err);
+                    None
+                }),
+            _ => None,
+        };
+        let faults = self.valid_faults(snn).or_else(|| plan.as_ref().map(|plan| plan.faults().to_vec()));
+        (plan, faults)
+    }
+
+    /// Chooses a random fault of a component of the input encoding, between the ones of this configuration.
+    fn random_input_fault(&self, input: &[(u128, Vec<u128>)]) -> Option<InputFault> {
+        let components: Vec<InputComponent> = self
+            .components
+            .iter()
+            .filter_map(|name| InputComponent::from_name(name))
+            .flatten()
+            .collect();
+        let component = *components.get(rand::thread_rng().gen_range(0..components.len().max(1)))?;
+        InputFault::random(component, input, &self.stuck_type)
+    }
+
+    /// Injects a fault in a random component, chosen between the ones of this configuration.
+    ///
+    /// Returns the outcome of the ECC if the fault hit an ECC protected word, and the fault to apply to
+    /// the input if the component is part of the input encoding.
+    fn inject_random_fault<M: Model + Clone>(
+        &self,
+        snn_tmp: &mut NN<M>,
+        input: &[(u128, Vec<u128>)],
+    ) -> (Option<EccOutcome>, Option<InputFault>) {
+        //select a random component between the one chosen by the user
+        let component = self.get_rand_component().to_lowercase();
+
+        if let Some(components) = InputComponent::from_name(&component) {
+            let component = components[rand::thread_rng().gen_range(0..components.len())];
+            return (None, InputFault::random(component, input, &self.stuck_type));
+        }
+
+        match &component as &str {
+            "neurons" | "n" | "neu" | "neuron" | "neuroni" | "neurone" => {
+                // println!("chose neuron");
+                let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
+                let rand_neuron_idx = rand::thread_rng()
+                    .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
+                let parameter = ["v_rest", "v_reset", "v_tau", "v_th"]
+                    .get(rand::thread_rng().gen_range(0..4))
+                    .unwrap()
+                    .to_string();
+                if let Some(num_bits) = self.ecc {
+                    let outcome = snn_tmp.layers[rand_layer_idx].stuck_bit_neuron_ecc(
                         self.stuck_type.clone(),
                         rand_neuron_idx,
-                        "full adder".to_string(),
-                    )
-                }
-                "comparatore" | "comparator" | "threshold" | "threashold comparator" => {
-                    // println!("chose comparator");
-                    let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
-                    let rand_neuron_idx = rand::thread_rng()
-                        .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
+                        parameter,
+                        num_bits,
+                    );
+                    return (outcome, None);
+                } else {
                     snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
                         self.stuck_type.clone(),
                         rand_neuron_idx,
-                        "comparator".to_string(),
-                    )
+                        parameter,
+                    );
                 }
-                _ => {
-                    println!("Error unknown component");
+            }
+            "weights" | "weight" | "w" | "pesi" | "peso" | "synapse" | "synapses" => {
+                let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
+                let layer = &mut snn_tmp.layers[rand_layer_idx];
+                let rand_from = rand::thread_rng().gen_range(0..layer.input_weights.nrows());
+                let rand_to = rand::thread_rng().gen_range(0..layer.input_weights.ncols());
+                if let Some(num_bits) = self.ecc {
+                    let outcome = layer.stuck_bit_weight_ecc(
+                        self.stuck_type.clone(),
+                        rand_from,
+                        rand_to,
+                        num_bits,
+                    );
+                    return (outcome, None);
+                } else {
+                    layer.stuck_bit_weight(self.stuck_type.clone(), rand_from, rand_to);
                 }
             }
-            let res = snn_tmp.solve_multiple_vec_spike(input.clone());
-            //println!("{:?}", res);
-            if are_equal(&res, &right_output) {
-                count_right_outputs += 1;
+            "vmem" | "potenziale di membrana" | "membrane potential" | "membrane" | "membrana" | "v_mem"=> {
+                // println!("chose vmem");
+                let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
+                let rand_neuron_idx = rand::thread_rng()
+                    .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
+                snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
+                    self.stuck_type.clone(),
+                    rand_neuron_idx,
+                    "v_mem".to_string(),
+                )
+            }
+
+            "fullAdder" | "full adder" | "full-adder" | "full_adder" | "adder" | "sommatore" => {
+                // println!("chose full adder");
+                let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
+                let rand_neuron_idx = rand::thread_rng()
+                    .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
+                snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
+                    self.stuck_type.clone(),
+                    rand_neuron_idx,
+                    "full adder".to_string(),
+                )
+            }
+            "comparatore" | "comparator" | "threshold" | "threashold comparator" => {
+                // println!("chose comparator");
+                let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
+                let rand_neuron_idx = rand::thread_rng()
+                    .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
+                snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
+                    self.stuck_type.clone(),
+                    rand_neuron_idx,
+                    "comparator".to_string(),
+                )
+            }
+            "reset" | "reset logic" | "reset unit" => {
+                let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
+                let rand_neuron_idx = rand::thread_rng()
+                    .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
+                snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
+                    self.stuck_type.clone(),
+                    rand_neuron_idx,
+                    "reset".to_string(),
+                )
+            }
+            "multiplier" | "moltiplicatore" | "mul" | "multiply" => {
+                let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
+                let rand_neuron_idx = rand::thread_rng()
+                    .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
+                snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
+                    self.stuck_type.clone(),
+                    rand_neuron_idx,
+                    "multiplier".to_string(),
+                )
+            }
+            "exponential" | "exp" | "exp unit" | "esponenziale" | "leak" => {
+                let rand_layer_idx = rand::thread_rng().gen_range(0..snn_tmp.get_num_layers());
+                let rand_neuron_idx = rand::thread_rng()
+                    .gen_range(0..snn_tmp.layers[rand_layer_idx].num_neurons());
+                snn_tmp.layers[rand_layer_idx].stuck_bit_neuron(
+                    self.stuck_type.clone(),
+                    rand_neuron_idx,
+                    "exponential".to_string(),
+                )
+            }
+            _ => {
+                println!("Error unknown component");
             }
         }
+        (None, None)
+    }
 
-        println!(
-            "For this SNN, running the stuck bit \"{:?}\" on {} for {} times, in {}% of cases the output is the same.\nThe process took {:?} to be finished.",
-            self.stuck_type,
-            self.components.join(", "),
-            self.times,
-            count_right_outputs as f64 / self.times as f64 * 100.0,
-            std::time::Instant::now()-time_init
-        );
-        return right_output;
+    /// The faults of the fault list that exist in the network, [None] if there is no fault list.
+    fn valid_faults<M: Model + Clone>(&self, snn: &NN<M>) -> Option<Vec<Fault>> {
+        self.faults.as_ref().map(|faults| {
+            faults
+                .iter()
+                .filter(|fault| match snn.clone().schedule_fault((*fault).clone()) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        println!("Skipping the fault {}: {}", fault, err);
+                        false
+                    }
+                })
+                .cloned()
+                .collect()
+        })
     }
 }
+
+/// Summary of the outcomes of a resilience test.
+#[derive(Debug, Clone)]
+pub struct ResilienceReport {
+    /// Components tested
+    pub components: Vec<String>,
+    /// Stuck type of the injected faults
+    pub stuck_type: Stuck,
+    /// Output of the network without faults
+    pub right_output: Vec<(u128, Vec<u128>)>,
+    /// The number of faults injected
+    pub times: u128,
+    /// The number of faulty runs whose output is the same of the fault free run
+    pub right_outputs: u128,
+    /// Outcomes of the faults injected in ECC protected words, [None] if ECC is disabled. The faults of a
+    /// fault list or of a sampling plan (see `fault_outcomes`) are not injected through the ECC, and leave
+    /// the counters at zero
+    pub ecc: Option<EccStats>,
+    /// The number of faulty runs with the range check whose output is the same of the fault free run,
+    /// [None] if the mitigation is disabled
+    pub mitigated_right_outputs: Option<u128>,
+    /// How many times the range check repaired a value, summed over all the runs
+    pub mitigation: Option<MitigationStats>,
+    /// Every fault of the fault list with true if the output was the same of the fault free run,
+    /// [None] if the faults were random
+    pub fault_outcomes: Option<Vec<(Fault, bool)>>,
+    /// How the faults were chosen, [None] for a fault list
+    pub sampling: Option<Sampling>,
+    /// Estimated failure rate (outputs different from the fault free run) with its standard error.
+    /// With stratified or importance sampling it is the failure rate over the whole fault space.
+    pub estimate: Estimate,
+    /// Time taken by the whole test
+    pub duration: Duration,
+}
+
+impl ResilienceReport {
+    /// Percentage of faulty runs whose output is the same of the fault free run.
+    pub fn right_outputs_percentage(&self) -> f64 {
+        self.right_outputs as f64 / self.times as f64 * 100.0
+    }
+
+    fn record_ecc(&mut self, outcome: Option<EccOutcome>) {
+        if let (Some(stats), Some(outcome)) = (self.ecc.as_mut(), outcome) {
+            stats.record(outcome);
+        }
+    }
+}
+
+impl fmt::Display for ResilienceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(Sampling::Stratified { .. } | Sampling::Importance(_)) = &self.sampling {
+            write!(
+                f,
+                "For this SNN, running the stuck bit \"{:?}\" on {} for {} faults chosen with {} sampling, in {}% of cases the output is the same.\nThe process took {:?} to be finished.",
+                self.stuck_type,
+                self.components.join(", "),
+                self.times,
+                if let Some(Sampling::Stratified { .. }) = &self.sampling { "stratified" } else { "importance" },
+                self.right_outputs_percentage(),
+                self.duration
+            )?;
+        } else if self.sampling.is_none() {
+            write!(
+                f,
+                "For this SNN, running a fault list of {} faults, in {}% of cases the output is the same.\nThe process took {:?} to be finished.",
+                self.times,
+                self.right_outputs_percentage(),
+                self.duration
+            )?;
+        } else {
+            write!(
+                f,
+                "For this SNN, running the stuck bit \"{:?}\" on {} for {} times, in {}% of cases the output is the same.\nThe process took {:?} to be finished.",
+                self.stuck_type,
+                self.components.join(", "),
+                self.times,
+                self.right_outputs_percentage(),
+                self.duration
+            )?;
+        }
+        if self.ecc.is_some() && self.fault_outcomes.is_some() {
+            write!(f, "\nECC: not applied to the faults of a fault list or of a sampling plan.")?;
+        } else if let Some(ecc) = &self.ecc {
+            write!(
+                f,
+                "\nECC: {} faults corrected, {} detected, {} uncorrected, {} without effect on the stored word.",
+                ecc.corrected, ecc.detected, ecc.uncorrected, ecc.no_error
+            )?;
+        }
+        if let (Some(right_outputs), Some(stats)) = (self.mitigated_right_outputs, &self.mitigation) {
+            write!(
+                f,
+                "\nWith the range check the output is the same in {}% of cases. Values repaired: {} v_mem, {} v_th, {} weights.",
+                right_outputs as f64 / self.times as f64 * 100.0,
+                stats.v_mem, stats.v_th, stats.weights
+            )?;
+        }
+        write!(f, "\nEstimated failure rate: {}", self.estimate)
+    }
+}
+
+/// Outcomes of a fault location injected as a permanent and as a transient fault.
+#[derive(Debug, Clone)]
+pub struct PairedOutcome {
+    pub permanent: Fault,
+    pub transient: Fault,
+    /// True if the output with the permanent fault differs from the fault free one
+    pub permanent_failed: bool,
+    /// True if the output with the transient fault differs from the fault free one
+    pub transient_failed: bool,
+}
+
+/// Summary of the comparison between permanent and transient faults in the same locations.
+#[derive(Debug, Clone)]
+pub struct DurationReport {
+    /// Output of the network without faults
+    pub right_output: Vec<(u128, Vec<u128>)>,
+    /// Outcomes of every fault location
+    pub outcomes: Vec<PairedOutcome>,
+    /// Time taken by the whole test
+    pub duration: Duration,
+}
+
+impl DurationReport {
+    /// Failure rate of the permanent faults.
+    pub fn permanent_failure_rate(&self) -> Estimate {
+        let failures = self.outcomes.iter().filter(|o| o.permanent_failed).count();
+        Estimate::binomial(failures as u128, self.outcomes.len() as u128)
+    }
+
+    /// Failure rate of the transient faults.
+    pub fn transient_failure_rate(&self) -> Estimate {
+        let failures = self.outcomes.iter().filter(|o| o.transient_failed).count();
+        Estimate::binomial(failures as u128, self.outcomes.len() as u128)
+    }
+
+    /// The locations whose output changes only with the permanent fault.
+    pub fn permanent_only(&self) -> Vec<&PairedOutcome> {
+        self.outcomes.iter().filter(|o| o.permanent_failed && !o.transient_failed).collect()
+    }
+
+    /// The locations whose output changes only with the transient fault.
+    pub fn transient_only(&self) -> Vec<&PairedOutcome> {
+        self.outcomes.iter().filter(|o| !o.permanent_failed && o.transient_failed).collect()
+    }
+
+    /// The number of locations whose output changes with both faults.
+    pub fn both_failed(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.permanent_failed && o.transient_failed).count()
+    }
+
+    /// The number of locations whose output is the same with both faults.
+    pub fn both_masked(&self) -> usize {
+        self.outcomes.iter().filter(|o| !o.permanent_failed && !o.transient_failed).count()
+    }
+}
+
+impl fmt::Display for DurationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "For this SNN, injecting {} fault locations as permanent and as transient faults:
+{} change the output with both, {} with none, {} only when permanent, {} only when transient.
+Permanent failure rate: {}
+Transient failure rate: {}
+The process took {:?} to be finished.",
+            self.outcomes.len(),
+            self.both_failed(),
+            self.both_masked(),
+            self.permanent_only().len(),
+            self.transient_only().len(),
+            self.permanent_failure_rate(),
+            self.transient_failure_rate(),
+            self.duration
+        )?;
+        for outcome in self.permanent_only() {
+            write!(f, "
+Only vulnerable to the permanent fault: {}", outcome.permanent)?;
+        }
+        Ok(())
+    }
+}
+
 pub fn are_equal(
     a: &Arc<Mutex<Vec<(u128, Vec<u128>)>>>,
     b: &Arc<Mutex<Vec<(u128, Vec<u128>)>>>,
@@ -0,0 +1,508 @@
+//! # Sampling of the Fault Space
+//!
+//! A uniform random campaign first picks a component and then a layer, a neuron and a bit, so the faults
+//! in small layers are sampled more often than their share of the fault space, and most of the trials
+//! hit mantissa bits that are almost always masked. This module samples the fault space in two other ways:
+//!
+//! - **stratified sampling**: the fault space is split in strata (by layer, component and/or bit field),
+//!   every stratum is sampled on its own with equal or proportional allocation, and the failure rates of
+//!   the strata are combined with their share of the fault space;
+//! - **importance sampling**: the faults are drawn with a probability proportional to user given weights
+//!   (e.g. more faults in the exponent bits), and every outcome is reweighted by the ratio between the
+//!   uniform probability of its fault and the probability it was drawn with.
+//!
+//! Both estimators are unbiased estimates of the failure rate over the whole fault space (every bit of every
+//! component counts once), and their standard error gives the confidence interval. Every stratum gets at
+//! least two faults, so that its variance can be estimated.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spiking_nn_resilience::snn::sampling::{Allocation, ImportanceWeights, Sampling, StratifyBy};
+//! use spiking_nn_resilience::snn::model::precision::BitField;
+//!
+//! let stratified = Sampling::Stratified {
+//!     by: vec![StratifyBy::Layer, StratifyBy::BitField],
+//!     allocation: Allocation::Proportional,
+//! };
+//! let importance = Sampling::Importance(ImportanceWeights::default().with_bit_field(BitField::Exponent, 10.0).unwrap());
+//! assert!(ImportanceWeights::default().with_bit_field(BitField::Sign, 0.0).is_err());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+use rand::Rng;
+
+use super::fault::{Fault, FaultComponent};
+use super::input_fault::InputComponent;
+use super::model::lif::{ComparatorOperand, ResetFault};
+use super::model::precision::BitField;
+use super::model::Stuck;
+use crate::{Model, NN};
+
+/// The minimum number of faults of a stratum, needed to estimate its variance.
+const MIN_PER_STRATUM: usize = 2;
+
+/// How the faults of a resilience test are chosen.
+#[derive(Debug, Clone, Default)]
+pub enum Sampling {
+    /// A random component between the ones of the test, then a random fault in it
+    #[default]
+    Uniform,
+    /// A number of faults in every stratum of the fault space
+    Stratified { by: Vec<StratifyBy>, allocation: Allocation },
+    /// Faults drawn with a probability proportional to the weights
+    Importance(ImportanceWeights),
+}
+
+/// Criterion splitting the fault space in strata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StratifyBy {
+    Layer,
+    Component,
+    BitField,
+}
+
+/// How the faults are divided between the strata. Every stratum gets at least one fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Allocation {
+    /// The same number of faults in every stratum
+    Equal,
+    /// A number of faults proportional to the size of the stratum
+    Proportional,
+}
+
+/// Relative probabilities of drawing a fault. The weight of a fault is the product of the weight of its
+/// bit field and of its component (1 if not given). The weights are finite and positive, so that every
+/// fault can be drawn and the estimate covers the whole fault space.
+#[derive(Debug, Clone, Default)]
+pub struct ImportanceWeights {
+    pub(crate) bit_fields: Vec<(BitField, f64)>,
+    pub(crate) components: Vec<(FaultComponent, f64)>,
+}
+
+/// Estimate of the failure rate (faults changing the output) over the whole fault space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub failure_rate: f64,
+    /// Standard error of the failure rate, infinite if it cannot be estimated because a stratum (or the
+    /// importance sample) has fewer than two outcomes.
+    pub std_error: f64,
+}
+
+/// The faults chosen by a [Sampling], with what is needed to weight their outcomes.
+#[derive(Debug, Clone)]
+pub struct SamplePlan {
+    pub(crate) faults: Vec<Fault>,
+    /// Stratum of every fault
+    pub(crate) strata: Vec<usize>,
+    /// Share of the fault space of every stratum
+    pub(crate) stratum_shares: Vec<f64>,
+    /// Ratio between the uniform probability of every fault and the probability it was drawn with
+    pub(crate) likelihood_ratios: Vec<f64>,
+    pub(crate) importance: bool,
+}
+
+/// A set of faults with the same layer, component and bit field. Its faults are equally likely.
+#[derive(Debug, Clone)]
+struct Cell {
+    layer: usize,
+    component: FaultComponent,
+    field: BitField,
+    bits: Vec<usize>,
+    /// neurons of the layer, or rows and columns of the input weights
+    units: (usize, usize),
+    /// inputs of every neuron of the layer
+    num_inputs: usize,
+}
+
+impl ImportanceWeights {
+    /// Sets the weight of the faults in a bit field.
+    ///
+    /// # Returns
+    ///
+    /// The weights, or an error if `weight` is not finite and positive.
+    pub fn with_bit_field(mut self, field: BitField, weight: f64) -> Result<Self, String> {
+        check_weight(weight)?;
+        self.bit_fields.retain(|(f, _)| *f != field);
+        self.bit_fields.push((field, weight));
+        Ok(self)
+    }
+
+    /// Sets the weight of the faults in a component.
+    ///
+    /// # Returns
+    ///
+    /// The weights, or an error if `weight` is not finite and positive.
+    pub fn with_component(mut self, component: FaultComponent, weight: f64) -> Result<Self, String> {
+        check_weight(weight)?;
+        self.components.retain(|(c, _)| *c != component);
+        self.components.push((component, weight));
+        Ok(self)
+    }
+
+    fn weight(&self, cell: &Cell) -> f64 {
+        let field = self.bit_fields.iter().find(|(f, _)| *f == cell.field).map(|(_, w)| *w);
+        let component = self.components.iter().find(|(c, _)| *c == cell.component).map(|(_, w)| *w);
+        field.unwrap_or(1.0) * component.unwrap_or(1.0)
+    }
+}
+
+/// Checks that an importance weight is finite and positive.
+fn check_weight(weight: f64) -> Result<(), String> {
+    if weight.is_finite() && weight > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("Invalid importance weight: {}, it must be finite and positive", weight))
+    }
+}
+
+impl Cell {
+    fn size(&self) -> f64 {
+        (self.units.0 * self.units.1 * self.bits.len()) as f64
+    }
+
+    fn key(&self, by: &[StratifyBy]) -> (Option<usize>, Option<String>, Option<BitField>) {
+        (
+            by.contains(&StratifyBy::Layer).then_some(self.layer),
+            by.contains(&StratifyBy::Component).then(|| format!("{:?}", self.component)),
+            by.contains(&StratifyBy::BitField).then_some(self.field),
+        )
+    }
+
+    /// Draws a fault of the cell, uniformly. The link of a heap is drawn too, so that the fault is the
+    /// same in every network it is injected in.
+    fn sample<R: Rng>(&self, stuck: &Stuck, rng: &mut R) -> Fault {
+        let bit = self.bits[rng.gen_range(0..self.bits.len())];
+        if self.component == FaultComponent::Weight {
+            let from = rng.gen_range(0..self.units.0);
+            let to = rng.gen_range(0..self.units.1);
+            Fault::new(self.layer, to, self.component, bit, stuck.clone()).from(from)
+        } else {
+            let neuron = rng.gen_range(0..self.units.1);
+            let fault = Fault::new(self.layer, neuron, self.component, bit, stuck.clone());
+            match self.component.num_links(self.num_inputs) {
+                Some(links) => fault.link(rng.gen_range(0..links)),
+                None => fault,
+            }
+        }
+    }
+}
+
+impl Sampling {
+    /// Chooses the faults of a resilience test.
+    ///
+    /// # Arguments
+    ///
+    /// * `snn` - The network to test.
+    /// * `components` - The components of the test, with the names accepted by the resilience test.
+    /// * `stuck` - The stuck type of the faults.
+    /// * `times` - The number of faults.
+    ///
+    /// # Returns
+    ///
+    /// The faults and their weights, [None] for uniform sampling, or an error if a component is unknown or
+    /// part of the input encoding.
+    pub fn plan<M: Model + Clone>(
+        &self,
+        snn: &NN<M>,
+        components: &[String],
+        stuck: &Stuck,
+        times: u128,
+    ) -> Result<Option<SamplePlan>, String> {
+        self.plan_with_rng(snn, components, stuck, times, &mut rand::thread_rng())
+    }
+
+    /// Chooses the faults of a resilience test with the given random number generator, so that a seeded
+    /// generator always chooses the same faults. See [Sampling::plan].
+    pub fn plan_with_rng<M: Model + Clone, R: Rng>(
+        &self,
+        snn: &NN<M>,
+        components: &[String],
+        stuck: &Stuck,
+        times: u128,
+        rng: &mut R,
+    ) -> Result<Option<SamplePlan>, String> {
+        if let Sampling::Uniform = self {
+            return Ok(None);
+        }
+        let cells = fault_space(snn, components)?;
+        let total: f64 = cells.iter().map(Cell::size).sum();
+        if total == 0.0 {
+            return Err("The fault space is empty".to_string());
+        }
+        let times = times as usize;
+
+        match self {
+            Sampling::Uniform => Ok(None),
+            Sampling::Stratified { by, allocation } => {
+                let mut strata: BTreeMap<_, Vec<&Cell>> = BTreeMap::new();
+                for cell in cells.iter() {
+                    strata.entry(cell.key(by)).or_default().push(cell);
+                }
+                let strata: Vec<Vec<&Cell>> = strata.into_values().collect();
+                let sizes: Vec<f64> = strata.iter().map(|s| s.iter().map(|c| c.size()).sum()).collect();
+                if strata.len() > 1 && times < MIN_PER_STRATUM * strata.len() {
+                    return Err(format!(
+                        "{} faults cannot be divided between {} strata, at least {} are needed",
+                        times,
+                        strata.len(),
+                        MIN_PER_STRATUM * strata.len()
+                    ));
+                }
+                let allocated = allocate(&sizes, times, *allocation);
+
+                let mut plan = SamplePlan::new(sizes.iter().map(|s| s / total).collect(), false);
+                for (h, (stratum, n)) in strata.iter().zip(allocated).enumerate() {
+                    let stratum_size = sizes[h];
+                    for _ in 0..n {
+                        let cell = pick(stratum, |c| c.size() / stratum_size, rng);
+                        plan.push(cell.sample(stuck, rng), h, 1.0);
+                    }
+                }
+                Ok(Some(plan))
+            }
+            Sampling::Importance(weights) => {
+                let weighted: f64 = cells.iter().map(|c| c.size() * weights.weight(c)).sum();
+                if weighted <= 0.0 {
+                    return Err("All the importance weights are zero".to_string());
+                }
+                let cells: Vec<&Cell> = cells.iter().collect();
+                let mut plan = SamplePlan::new(vec![1.0], true);
+                for _ in 0..times {
+                    let cell = pick(&cells, |c| c.size() * weights.weight(c) / weighted, rng);
+                    // uniform probability 1/total, drawn with probability weight/weighted
+                    plan.push(cell.sample(stuck, rng), 0, weighted / (total * weights.weight(cell)));
+                }
+                Ok(Some(plan))
+            }
+        }
+    }
+}
+
+impl SamplePlan {
+    fn new(stratum_shares: Vec<f64>, importance: bool) -> Self {
+        SamplePlan {
+            faults: vec![],
+            strata: vec![],
+            stratum_shares,
+            likelihood_ratios: vec![],
+            importance,
+        }
+    }
+
+    fn push(&mut self, fault: Fault, stratum: usize, likelihood_ratio: f64) {
+        self.faults.push(fault);
+        self.strata.push(stratum);
+        self.likelihood_ratios.push(likelihood_ratio);
+    }
+
+    pub fn faults(&self) -> &[Fault] {
+        &self.faults
+    }
+
+    /// Estimates the failure rate of the whole fault space.
+    ///
+    /// # Arguments
+    ///
+    /// * `failures` - For every fault of the plan, true if it changed the output of the network.
+    pub fn estimate(&self, failures: &[bool]) -> Estimate {
+        if self.importance {
+            let values: Vec<f64> = failures
+                .iter()
+                .zip(self.likelihood_ratios.iter())
+                .map(|(failure, ratio)| if *failure { *ratio } else { 0.0 })
+                .collect();
+            let (mean, variance) = mean_and_variance(&values);
+            let std_error = if values.len() < MIN_PER_STRATUM {
+                f64::INFINITY
+            } else {
+                (variance / values.len() as f64).sqrt()
+            };
+            return Estimate {
+                failure_rate: mean,
+                std_error,
+            };
+        }
+
+        let mut failure_rate = 0.0;
+        let mut variance = 0.0;
+        for (h, share) in self.stratum_shares.iter().enumerate() {
+            let outcomes: Vec<bool> = failures
+                .iter()
+                .zip(self.strata.iter())
+                .filter(|(_, s)| **s == h)
+                .map(|(f, _)| *f)
+                .collect();
+            if outcomes.is_empty() {
+                variance = f64::INFINITY;
+                continue;
+            }
+            let n = outcomes.len() as f64;
+            let p = outcomes.iter().filter(|f| **f).count() as f64 / n;
+            failure_rate += share * p;
+            variance += if outcomes.len() < MIN_PER_STRATUM {
+                // the variance of the stratum cannot be estimated
+                f64::INFINITY
+            } else {
+                share * share * p * (1.0 - p) / (n - 1.0)
+            };
+        }
+        Estimate {
+            failure_rate,
+            std_error: variance.sqrt(),
+        }
+    }
+}
+
+impl Estimate {
+    /// Estimate of a simple random sample with `failures` failures in `n` trials.
+    pub fn binomial(failures: u128, n: u128) -> Estimate {
+        let n = n.max(1) as f64;
+        let p = failures as f64 / n;
+        Estimate {
+            failure_rate: p,
+            std_error: (p * (1.0 - p) / n).sqrt(),
+        }
+    }
+
+    /// 95% confidence interval of the failure rate, clamped to [0, 1].
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let margin = 1.96 * self.std_error;
+        ((self.failure_rate - margin).max(0.0), (self.failure_rate + margin).min(1.0))
+    }
+}
+
+impl Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.std_error.is_finite() {
+            return write!(f, "{:.2}% (confidence interval unknown)", self.failure_rate * 100.0);
+        }
+        let (low, high) = self.confidence_interval();
+        write!(
+            f,
+            "{:.2}% (95% confidence interval: {:.2}% - {:.2}%)",
+            self.failure_rate * 100.0,
+            low * 100.0,
+            high * 100.0
+        )
+    }
+}
+
+/// The components hit by the faults of a component of the resilience test.
+fn fault_components(name: &str) -> Result<Vec<FaultComponent>, String> {
+    Ok(match name.to_lowercase().as_str() {
+        "neurons" | "n" | "neu" | "neuron" | "neuroni" | "neurone" => vec![
+            FaultComponent::VRest,
+            FaultComponent::VReset,
+            FaultComponent::Tau,
+            FaultComponent::VTh,
+        ],
+        "weights" | "weight" | "w" | "pesi" | "peso" | "synapse" | "synapses" => vec![FaultComponent::Weight],
+        "vmem" | "potenziale di membrana" | "membrane potential" | "membrane" | "membrana" | "v_mem" => {
+            vec![FaultComponent::VMem]
+        }
+        "fulladder" | "full adder" | "full-adder" | "full_adder" | "adder" | "sommatore" => {
+            vec![FaultComponent::FullAdder]
+        }
+        "comparatore" | "comparator" | "threshold" | "threashold comparator" => vec![
+            FaultComponent::Comparator(ComparatorOperand::VMem),
+            FaultComponent::Comparator(ComparatorOperand::VTh),
+        ],
+        // only the corrupted reset has bits to sample
+        "reset" | "reset logic" | "reset unit" => vec![FaultComponent::Reset(ResetFault::Corrupted)],
+        "multiplier" | "moltiplicatore" | "mul" | "multiply" => vec![FaultComponent::Multiplier],
+        "exponential" | "exp" | "exp unit" | "esponenziale" | "leak" => vec![FaultComponent::Exponential],
+        _ => return Err(format!("Unknown component: {}", name)),
+    })
+}
+
+/// Splits the fault space of the network in cells of equally likely faults. The components of the input
+/// encoding are not part of the network, and give an error.
+fn fault_space<M: Model + Clone>(snn: &NN<M>, components: &[String]) -> Result<Vec<Cell>, String> {
+    let mut selected = vec![];
+    for name in components.iter() {
+        if InputComponent::from_name(name).is_some() {
+            return Err(format!("The input encoding ({}) is not part of the fault space of the network", name));
+        }
+        for component in fault_components(name)? {
+            if !selected.contains(&component) {
+                selected.push(component);
+            }
+        }
+    }
+
+    let mut cells = vec![];
+    for (layer_idx, layer) in snn.layers.iter().enumerate() {
+        for component in selected.iter() {
+            let (layout, units) = if *component == FaultComponent::Weight {
+                (layer.weight_layout(), (layer.input_weights.nrows(), layer.input_weights.ncols()))
+            } else {
+                (M::word_layout(&layer.neurons[0]), (1, layer.num_neurons()))
+            };
+            for field in [BitField::Sign, BitField::Exponent, BitField::Mantissa] {
+                let bits = layout.bits(field);
+                if !bits.is_empty() {
+                    cells.push(Cell {
+                        layer: layer_idx,
+                        component: *component,
+                        field,
+                        bits,
+                        units,
+                        num_inputs: layer.input_weights.nrows(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(cells)
+}
+
+/// Divides `times` samples between strata of the given sizes, at least [MIN_PER_STRATUM] each when
+/// there are enough samples.
+fn allocate(sizes: &[f64], times: usize, allocation: Allocation) -> Vec<usize> {
+    let strata = sizes.len();
+    let base = (times / strata).min(MIN_PER_STRATUM);
+    let extra = times - base * strata;
+    let total: f64 = sizes.iter().sum();
+    let shares: Vec<f64> = match allocation {
+        Allocation::Equal => vec![extra as f64 / strata as f64; strata],
+        Allocation::Proportional => sizes.iter().map(|s| extra as f64 * s / total).collect(),
+    };
+
+    // largest remainder method
+    let mut allocated: Vec<usize> = shares.iter().map(|s| base + s.floor() as usize).collect();
+    let mut remainders: Vec<(usize, f64)> = shares.iter().map(|s| s - s.floor()).enumerate().collect();
+    remainders.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let missing = times.saturating_sub(allocated.iter().sum());
+    for (h, _) in remainders.into_iter().take(missing) {
+        allocated[h] += 1;
+    }
+    allocated
+}
+
+/// Picks an element with the given probabilities.
+fn pick<'a, R: Rng>(cells: &[&'a Cell], probability: impl Fn(&Cell) -> f64, rng: &mut R) -> &'a Cell {
+    let mut r = rng.gen::<f64>();
+    for cell in cells {
+        r -= probability(cell);
+        if r < 0.0 {
+            return cell;
+        }
+    }
+    cells[cells.len() - 1]
+}
+
+fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() == 1 {
+        return (mean, 0.0);
+    }
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance)
+}
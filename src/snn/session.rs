@@ -0,0 +1,255 @@
+//! # Simulation Sessions
+//!
+//! Every engine of [NN](crate::NN) simulates clones of the layers and drops them at the end, so a
+//! simulation always starts from the initial state of the neurons. A [Session], created with
+//! [NN::session](crate::NN::session), owns the state of the network instead: the input can be fed in
+//! segments with [Session::feed], and the network is simulated up to a given time with
+//! [Session::run_until], or one time at a time with [Session::step], carrying the membrane potentials from
+//! a segment to the next one. [Session::reset] restores the initial state.
+//!
+//! The pending spikes are held in a priority queue ordered by arrival time and receiving layer, as in
+//! [NN::solve_event_driven](crate::NN::solve_event_driven), but at every time all the neurons of a
+//! receiving layer are updated: the whole input run in a session gives the output of
+//! [NN::solve_sequential](crate::NN::solve_sequential), also with the delays of the synapses (see
+//! [NN::use_delays](crate::NN::use_delays)). The times are absolute, and a spike fed with a time already
+//! simulated is received at the current time of the session.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use nalgebra::DMatrix;
+//! use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+//! use spiking_nn_resilience::NN;
+//!
+//! // the neuron fires after two input spikes
+//! let nn = NN::<LeakyIntegrateFire>::new()
+//!     .layer(
+//!         vec![LifNeuron::new(0.0, 0.0, 1.5, 1000.0)],
+//!         DMatrix::from_vec(1, 1, vec![1.0]),
+//!         DMatrix::from_vec(1, 1, vec![0.0]),
+//!     )
+//!     .unwrap();
+//!
+//! let mut session = nn.session();
+//! session.feed(vec![(0, vec![1])]);
+//! assert!(session.run_until(5).is_silent());
+//! // the membrane potential is carried to the next segment
+//! session.feed(vec![(0, vec![6])]);
+//! assert_eq!(session.run_until(10).spikes(0), &[7]);
+//!
+//! session.reset();
+//! session.feed(vec![(0, vec![6])]);
+//! assert!(session.run_until(10).is_silent());
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::layer::Layer;
+use super::output::SimulationOutput;
+use super::probe::ProbeTrace;
+use super::recorder::SpikeRecorder;
+use super::Spike;
+use crate::{Model, NN};
+
+/// A network simulated in segments, holding the state of its neurons between them.
+#[derive(Clone)]
+pub struct Session<M: Model + Clone + 'static> {
+    /// The layers in their initial state, restored by [Session::reset]
+    initial: Vec<Layer<M>>,
+    layers: Vec<Layer<M>>,
+    /// The pending spikes, with arrival time and receiving layer
+    queue: BinaryHeap<Reverse<(u128, usize, Spike)>>,
+    /// The first time not yet simulated
+    time: u128,
+}
+
+impl<M: Model + Clone> Session<M> {
+    /// Creates a session of the network, with the neurons in their initial state.
+    pub fn new(nn: &NN<M>) -> Session<M> {
+        Session {
+            initial: nn.layers.clone(),
+            layers: nn.layers.clone(),
+            queue: BinaryHeap::new(),
+            time: 0,
+        }
+    }
+
+    /// The first time not yet simulated.
+    pub fn time(&self) -> u128 {
+        self.time
+    }
+
+    /// The layers of the network, with the current state of their neurons.
+    pub fn layers(&self) -> &[Layer<M>] {
+        &self.layers
+    }
+
+    /// True if no spike is waiting to be handled.
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Adds a segment of input spikes, received by the first layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Vector of tuples representing neuron indices and their corresponding vectors of spikes.
+    pub fn feed(&mut self, input: Vec<(u128, Vec<u128>)>) {
+        for spike in Spike::vec_of_all_spikes(input) {
+            self.receive(spike);
+        }
+    }
+
+    /// Adds an input spike, received by the first layer.
+    pub(crate) fn receive(&mut self, spike: Spike) {
+        let ts = spike.ts.max(self.time);
+        NN::schedule(&mut self.queue, &self.layers[0], 0, Spike::new(ts, 0, spike.neuron_id));
+    }
+
+    /// The time of the next pending spike, or [None] if no spike is pending.
+    pub fn next_time(&self) -> Option<u128> {
+        self.queue.peek().map(|Reverse((ts, _, _))| *ts)
+    }
+
+    /// Simulates the next time with pending spikes, in all the layers receiving spikes at that time.
+    ///
+    /// # Returns
+    ///
+    /// The spikes of the output neurons fired at that time, or [None] if no spike is pending.
+    pub fn step(&mut self) -> Option<Vec<Spike>> {
+        let ts = self.next_time()?;
+        let mut output = vec![];
+        while self.next_time() == Some(ts) {
+            let Reverse((_, layer_idx, first)) = self.queue.pop().unwrap();
+            let input_spike = self.pop_group(ts, layer_idx, first);
+            self.handle(ts, layer_idx, &input_spike, &mut output);
+        }
+        self.time = ts.saturating_add(1);
+        Some(output)
+    }
+
+    /// Simulates the pending spikes that no input spike later than `ts` can precede: the ones received by
+    /// the layer at index `L` up to time `ts + L`, since a spike takes at least a time to cross a layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `ts` - The time of the last input spikes.
+    ///
+    /// # Returns
+    ///
+    /// The spikes of the output neurons fired by the simulated spikes.
+    pub(crate) fn run_settled(&mut self, ts: u128) -> Vec<Spike> {
+        let mut output = vec![];
+        let mut unsettled = vec![];
+        while let Some(Reverse((spike_ts, layer_idx, first))) = self.queue.pop() {
+            if spike_ts > ts.saturating_add(layer_idx as u128) {
+                unsettled.push(Reverse((spike_ts, layer_idx, first)));
+                continue;
+            }
+            let input_spike = self.pop_group(spike_ts, layer_idx, first);
+            self.handle(spike_ts, layer_idx, &input_spike, &mut output);
+        }
+        self.queue.extend(unsettled);
+        self.time = self.time.max(ts.saturating_add(1));
+        output
+    }
+
+    /// Pops the other pending spikes received by the layer at index `layer_idx` at time `ts`.
+    fn pop_group(&mut self, ts: u128, layer_idx: usize, first: Spike) -> Vec<Spike> {
+        let mut input_spike = vec![first];
+        while let Some(Reverse((next_ts, next_layer, _))) = self.queue.peek() {
+            if *next_ts != ts || *next_layer != layer_idx {
+                break;
+            }
+            input_spike.push(self.queue.pop().unwrap().0 .2);
+        }
+        input_spike
+    }
+
+    /// Updates the layer at index `layer_idx` with the spikes received at time `ts`, scheduling its spikes
+    /// to the next layer or adding them to `output` for the last layer.
+    fn handle(&mut self, ts: u128, layer_idx: usize, input_spike: &[Spike], output: &mut Vec<Spike>) {
+        let is_last = layer_idx == self.layers.len() - 1;
+        let layer_output = NN::step_layer(
+            &mut self.layers[layer_idx],
+            layer_idx,
+            input_spike,
+            ts,
+            ts + 1,
+            is_last,
+            false,
+        );
+        if is_last {
+            output.extend(layer_output);
+        } else {
+            for s in layer_output {
+                NN::schedule(&mut self.queue, &self.layers[layer_idx + 1], layer_idx + 1, s);
+            }
+        }
+    }
+
+    /// Simulates the network up to time `ts` included. The spikes arriving later stay pending.
+    ///
+    /// # Arguments
+    ///
+    /// * `ts` - The last time to simulate.
+    ///
+    /// # Returns
+    ///
+    /// The spike trains of the output neurons in the simulated times, with the samples of the probes.
+    pub fn run_until(&mut self, ts: u128) -> SimulationOutput {
+        let mut output = SimulationOutput::new(self.layers.last().unwrap().num_neurons());
+        while self.next_time().is_some_and(|next_ts| next_ts <= ts) {
+            for s in self.step().unwrap() {
+                output.push(s.neuron_id, s.ts);
+            }
+        }
+        self.time = self.time.max(ts.saturating_add(1));
+        output.probes = self.take_probes();
+        output.recording = self.take_recording();
+        output
+    }
+
+    /// Simulates the network until no spike is pending.
+    ///
+    /// # Returns
+    ///
+    /// The spike trains of the output neurons, with the samples of the probes.
+    pub fn run(&mut self) -> SimulationOutput {
+        let mut output = SimulationOutput::new(self.layers.last().unwrap().num_neurons());
+        while let Some(layer_output) = self.step() {
+            for s in layer_output {
+                output.push(s.neuron_id, s.ts);
+            }
+        }
+        output.probes = self.take_probes();
+        output.recording = self.take_recording();
+        output
+    }
+
+    /// Restores the initial state of the neurons and drops the pending spikes.
+    pub fn reset(&mut self) {
+        self.layers = self.initial.clone();
+        self.queue.clear();
+        self.time = 0;
+    }
+
+    /// The samples of the probes taken since the last call, leaving the probes in place.
+    fn take_probes(&mut self) -> Vec<ProbeTrace> {
+        self.layers
+            .iter_mut()
+            .flat_map(|layer| layer.probes.iter_mut())
+            .map(|trace| std::mem::replace(trace, ProbeTrace::new(trace.layer, trace.neuron)))
+            .collect()
+    }
+
+    /// The spikes recorded since the last call, leaving the recorder in place.
+    fn take_recording(&mut self) -> Option<SpikeRecorder> {
+        SpikeRecorder::merge(
+            self.layers
+                .iter_mut()
+                .filter_map(|layer| layer.recorder.as_mut().map(SpikeRecorder::take)),
+        )
+    }
+}
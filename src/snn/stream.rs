@@ -0,0 +1,91 @@
+//! # Streaming Simulation
+//!
+//! The other engines of [NN](crate::NN) need the whole input upfront and return the output at the end. A
+//! [SpikeStream], created with [NN::stream](crate::NN::stream), takes the input spikes from an iterator,
+//! also an unbounded one such as the receiver of a channel fed by an event camera, and is itself an
+//! iterator over the output spikes, produced while the input is read.
+//!
+//! The input spikes must be sorted by time. All the spikes with the same time are received together by the
+//! first layer, so the spikes of a time are handled when the first spike of a later time arrives, or when
+//! the input ends. The network is simulated in a [Session](super::session::Session), with the same
+//! semantics of [NN::solve_sequential](crate::NN::solve_sequential): once the spikes of a time are received,
+//! every spike that no later input can precede is handled at once, in all the layers, and only the spikes
+//! delayed by the synapses wait for the next input. The stream only keeps the state of the neurons and the
+//! spikes not yet handled: the memory does not grow with the length of the input. A spike older than the
+//! previous ones is received with the spikes of the current time, or at the current time of the session
+//! once those are handled.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use nalgebra::DMatrix;
+//! use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+//! use spiking_nn_resilience::snn::Spike;
+//! use spiking_nn_resilience::NN;
+//! use std::sync::mpsc;
+//!
+//! let nn = NN::<LeakyIntegrateFire>::new()
+//!     .layer(
+//!         vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0)],
+//!         DMatrix::from_vec(1, 1, vec![1.0]),
+//!         DMatrix::from_vec(1, 1, vec![0.0]),
+//!     )
+//!     .unwrap();
+//!
+//! let (tx, rx) = mpsc::channel();
+//! for ts in [1, 3] {
+//!     tx.send(Spike::new(ts, 0, 0)).unwrap();
+//! }
+//! drop(tx);
+//! let output: Vec<u128> = nn.stream(rx).map(|spike| spike.ts).collect();
+//! assert_eq!(output, vec![2, 4]);
+//! ```
+
+use std::collections::VecDeque;
+use std::iter::Peekable;
+
+use super::session::Session;
+use super::Spike;
+use crate::Model;
+
+/// A simulation reading the input spikes from an iterator and producing the output spikes incrementally.
+pub struct SpikeStream<M: Model + Clone + 'static, I: Iterator<Item = Spike>> {
+    /// The state of the network, carried from a time to the next one
+    session: Session<M>,
+    input: Peekable<I>,
+    /// Output spikes not yet returned
+    pending: VecDeque<Spike>,
+}
+
+impl<M: Model + Clone, I: Iterator<Item = Spike>> SpikeStream<M, I> {
+    pub(crate) fn new(session: Session<M>, input: I) -> SpikeStream<M, I> {
+        SpikeStream {
+            session,
+            input: input.peekable(),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<M: Model + Clone, I: Iterator<Item = Spike>> Iterator for SpikeStream<M, I> {
+    /// A spike of an output neuron, with the time it is fired plus one as in the other engines
+    type Item = Spike;
+
+    fn next(&mut self) -> Option<Spike> {
+        while self.pending.is_empty() {
+            match self.input.next() {
+                Some(first) => {
+                    let ts = first.ts.max(self.session.time());
+                    self.session.receive(Spike::new(ts, 0, first.neuron_id));
+                    while let Some(spike) = self.input.next_if(|spike| spike.ts <= ts) {
+                        self.session.receive(Spike::new(ts, 0, spike.neuron_id));
+                    }
+                    self.pending.extend(self.session.run_settled(ts));
+                }
+                // the end of the input
+                None => self.pending.extend(self.session.step()?),
+            }
+        }
+        self.pending.pop_front()
+    }
+}
@@ -0,0 +1,61 @@
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::nn::SimulationMode;
+use spiking_nn_resilience::snn::recorder::SpikeRecorder;
+
+#[test]
+fn test_batch_in_order() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    // the input shifted in time and with fewer input neurons
+    let inputs: Vec<Vec<(u128, Vec<u128>)>> = (0..20)
+        .map(|i| {
+            input
+                .iter()
+                .skip(i % input.len())
+                .map(|(n, spikes)| (*n, spikes.iter().map(|ts| ts + i as u128).collect()))
+                .collect()
+        })
+        .collect();
+
+    for mode in [SimulationMode::Sequential, SimulationMode::Threaded] {
+        let outputs = nn.solve_batch(&inputs, mode, 4);
+        assert_eq!(outputs.len(), inputs.len());
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(output, nn.solve(input.clone(), mode));
+        }
+    }
+}
+
+#[test]
+fn test_batch_workers() {
+    let mut nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let inputs = vec![input.clone(), vec![], input];
+    nn.use_recorder(SpikeRecorder::with_layers(vec![nn.layers.len() - 1]));
+
+    // no worker is run as one, more workers than inputs as one per input
+    let outputs = nn.solve_batch(&inputs, SimulationMode::EventDriven, 0);
+    assert_eq!(
+        outputs,
+        nn.solve_batch(&inputs, SimulationMode::EventDriven, 16)
+    );
+    assert!(outputs[1].is_silent());
+    assert_eq!(outputs[0], outputs[2]);
+    // every output records the spikes of its own input only
+    for output in outputs.iter() {
+        assert_eq!(output.recording().unwrap().spikes().len(), output.total_spikes());
+    }
+    assert!(nn
+        .solve_batch(&[], SimulationMode::Sequential, 4)
+        .is_empty());
+}
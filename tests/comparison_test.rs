@@ -0,0 +1,91 @@
+use nalgebra::DMatrix;
+use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::precision::Precision;
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::{Resilience, NN};
+
+#[test]
+fn test_compare_same_network() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let components = ["adder", "multiplier"].map(String::from).to_vec();
+    let resilience = Resilience::new(components, Stuck::Transient, 100);
+
+    let report = resilience.compare(nn.clone(), nn.clone(), input.clone(), 42).unwrap();
+    assert_eq!(report.outcomes.len(), 100);
+    // the link of the heap is part of the sampled fault, so both networks get the same one
+    assert!(report.outcomes.iter().all(|o| o.fault.link.is_some()));
+    assert_eq!((report.only_a_failed(), report.only_b_failed()), (0, 0));
+    assert_eq!(report.a, report.b);
+    assert!((report.p_value() - 1.0).abs() < 1e-6);
+
+    // the same seed gives the same faults
+    let again = resilience.compare(nn.clone(), nn.clone(), input.clone(), 42).unwrap();
+    let faults = |r: &spiking_nn_resilience::snn::comparison::ComparisonReport| {
+        r.outcomes.iter().map(|o| o.fault.to_string()).collect::<Vec<_>>()
+    };
+    assert_eq!(faults(&report), faults(&again));
+
+    let other = NeuronJson::read_from_file(
+        "./tests/layers3.json",
+        "./tests/weights3.json",
+        "./tests/configurations3.json",
+    )
+    .unwrap();
+    assert!(resilience.compare(nn.clone(), other, input.clone(), 42).is_err());
+    // the input encoding cannot be injected in the networks
+    let inputs = Resilience::new(vec!["vmem".to_string(), "input".to_string()], Stuck::One, 10);
+    assert!(inputs.compare(nn.clone(), nn, input, 42).is_err());
+}
+
+#[test]
+fn test_compare_variants() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let mut f32_nn = nn.clone();
+    f32_nn.use_precision(Precision::F32);
+
+    let report = Resilience::new(vec!["neurons".to_string()], Stuck::One, 40)
+        .compare(nn, f32_nn, input, 7)
+        .unwrap();
+    // the faults in the bits of a f64 missing in a f32 are skipped
+    assert!(report.outcomes.len() <= 40);
+    assert!(report.outcomes.iter().all(|o| o.fault.bit < 32));
+
+    // the same neuron with a weight of 1 or 2: clearing bit 62 of the weight stops the second one only,
+    // clearing bit 61 the first one only
+    let network = |weight: f64| {
+        NN::<LeakyIntegrateFire>::new()
+            .layer(
+                vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0)],
+                DMatrix::from_vec(1, 1, vec![weight]),
+                DMatrix::from_vec(1, 1, vec![0.0]),
+            )
+            .unwrap()
+    };
+    let weight_fault = |bit| Fault::new(0, 0, FaultComponent::Weight, bit, Stuck::Zero).from(0);
+    let mut faults = vec![weight_fault(62); 9];
+    faults.push(weight_fault(61));
+    let report = Resilience::new(vec![], Stuck::Zero, 0)
+        .with_faults(faults)
+        .compare(network(1.0), network(2.0), vec![(0, vec![1, 3])], 7)
+        .unwrap();
+    assert_eq!((report.only_a_failed(), report.only_b_failed()), (1, 9));
+    assert_eq!((report.a.failure_rate, report.b.failure_rate), (0.1, 0.9));
+    // McNemar's test: z = (9 - 1) / sqrt(9 + 1)
+    assert!((report.z_score() - 8.0 / 10f64.sqrt()).abs() < 1e-12);
+    assert!((report.p_value() - 0.011412).abs() < 1e-5, "{}", report.p_value());
+    assert!(report.is_significant(0.05) && !report.is_significant(0.01));
+}
@@ -0,0 +1,69 @@
+use spiking_nn_resilience::snn::dataset::{classify, Dataset, SweepFault};
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent};
+use spiking_nn_resilience::snn::json_adapter::NeuronJson;
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::Resilience;
+
+#[test]
+fn test_read_dataset() {
+    let dataset = Dataset::read_from_dir("./tests/dataset").unwrap();
+    assert_eq!(dataset.len(), 3);
+    assert_eq!(dataset.names()[0], "input_0.json");
+    assert_eq!(dataset.inputs()[1][1].1.len(), 10);
+    assert!(Dataset::read_from_dir("./tests/missing").is_err());
+
+    assert_eq!(classify(&[(0, vec![1, 2]), (1, vec![1, 2, 3]), (2, vec![4])]), Some(1));
+    assert_eq!(classify(&[(0, vec![3]), (1, vec![1])]), Some(0));
+    assert_eq!(classify(&[(0, vec![]), (1, vec![])]), None);
+}
+
+#[test]
+fn test_dataset_campaign() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let dataset = Dataset::read_from_dir("./tests/dataset").unwrap();
+
+    let faults = vec![
+        // a negative threshold makes the output neuron fire at every step
+        Fault::new(3, 0, FaultComponent::VTh, 63, Stuck::One),
+        Fault::new(0, 0, FaultComponent::VTh, 63, Stuck::One),
+    ];
+    let report = Resilience::new(vec![], Stuck::Zero, 0)
+        .with_faults(faults)
+        .run_dataset_test(nn.clone(), &dataset);
+    assert_eq!(report.right_classes, vec![Some(1); 3]);
+    assert_eq!(report.faults[0].misclassified, vec![0, 1, 2]);
+    assert!(report.faults[1].misclassified.is_empty());
+    assert_eq!(report.masked_faults(), 1);
+    assert_eq!(report.mean_misclassification_rate(), 0.5);
+
+    let report = Resilience::new(vec!["neurons".to_string(), "weights".to_string()], Stuck::Transient, 5)
+        .run_dataset_test(nn.clone(), &dataset);
+    assert_eq!(report.faults.len(), 5);
+    // the random faults are reported, so that they can be injected again
+    assert!(report
+        .faults
+        .iter()
+        .all(|sweep| matches!(sweep.fault, SweepFault::Network(_))));
+    assert!(report.masked_faults() + report.input_dependent_faults() <= 5);
+
+    // the faults of the input encoding are reported too
+    let report = Resilience::new(vec!["vmem".to_string(), "input".to_string()], Stuck::One, 40)
+        .run_dataset_test(nn, &dataset);
+    assert_eq!(report.faults.len(), 40);
+    let input_faults = report
+        .faults
+        .iter()
+        .filter(|sweep| matches!(sweep.fault, SweepFault::Input(_)))
+        .count();
+    assert!(input_faults > 0 && input_faults < 40);
+    assert!(report.faults.iter().all(|sweep| match &sweep.fault {
+        SweepFault::Network(fault) => fault.component == FaultComponent::VMem,
+        SweepFault::Input(_) => true,
+    }));
+    assert!(!report.to_string().contains("Fault "));
+}
@@ -0,0 +1,118 @@
+use nalgebra::DMatrix;
+use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::nn::SimulationMode;
+use spiking_nn_resilience::snn::Spike;
+use spiking_nn_resilience::NN;
+
+#[test]
+fn test_delays_in_all_engines() {
+    // v_rest 0, v_reset 0, v_th 0.5, tau 1: every neuron fires at every input spike
+    let mut nn = NN::<LeakyIntegrateFire>::new()
+        .layer(
+            vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0)],
+            DMatrix::from_vec(1, 1, vec![1.0]),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+        )
+        .unwrap()
+        .layer(
+            vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0); 2],
+            DMatrix::from_vec(1, 2, vec![1.0, 1.0]),
+            DMatrix::from_vec(2, 2, vec![0.0; 4]),
+        )
+        .unwrap();
+    assert!(nn
+        .use_delays(1, DMatrix::from_vec(2, 1, vec![1, 3]))
+        .is_err());
+    assert!(nn
+        .use_delays(1, DMatrix::from_vec(1, 2, vec![0, 3]))
+        .is_err());
+    assert!(nn
+        .use_delays(2, DMatrix::from_vec(1, 2, vec![1, 3]))
+        .is_err());
+    nn.use_delays(1, DMatrix::from_vec(1, 2, vec![1, 3]))
+        .unwrap();
+    assert_eq!(nn.layers[1].get_delay(0, 1), Some(3));
+    assert_eq!(nn.layers[0].get_delay(0, 0), Some(1));
+    assert_eq!(nn.layers[1].get_delay(1, 0), None);
+
+    // the spike fired by the first layer at 1 reaches the second neuron two steps later than the first one
+    let input = vec![(0, vec![1])];
+    let expected = vec![(0, vec![3]), (1, vec![5])];
+    for mode in [
+        SimulationMode::Threaded,
+        SimulationMode::Sequential,
+        SimulationMode::EventDriven,
+        SimulationMode::ClockDriven { dt: 1 },
+    ] {
+        assert_eq!(nn.solve(input.clone(), mode), expected, "{:?}", mode);
+    }
+    let mut session = nn.session();
+    session.feed(input.clone());
+    assert_eq!(session.run_until(3), vec![(0, vec![3]), (1, vec![])]);
+    assert_eq!(session.run(), vec![(0, vec![]), (1, vec![5])]);
+    let streamed: Vec<Spike> = nn.stream(Spike::vec_of_all_spikes(input.clone())).collect();
+    assert_eq!(streamed, vec![Spike::new(3, 1, 0), Spike::new(5, 1, 1)]);
+    // a delay is a number of ticks of the clock
+    assert_eq!(
+        nn.solve_clock_driven(input.clone(), 2),
+        vec![(0, vec![6]), (1, vec![10])]
+    );
+
+    // the delay of the input synapses shifts the input
+    nn.use_delays(0, DMatrix::from_vec(1, 1, vec![2])).unwrap();
+    assert_eq!(
+        nn.solve_sequential(input.clone()),
+        vec![(0, vec![4]), (1, vec![6])]
+    );
+    assert_eq!(
+        nn.solve_event_driven(input),
+        vec![(0, vec![4]), (1, vec![6])]
+    );
+}
+
+#[test]
+fn test_delays_from_json() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let delayed = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights_delays.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    assert_eq!(delayed.layers[1].get_delay(2, 0), Some(3));
+    assert_eq!(delayed.layers[2].get_delay(0, 0), Some(1));
+
+    let output = delayed.solve_sequential(input.clone());
+    assert_ne!(output, nn.solve_sequential(input.clone()));
+    assert_eq!(output, delayed.solve_threaded(input.clone()));
+    let streamed = delayed.stream(Spike::vec_of_all_spikes(input.clone()));
+    assert_eq!(streamed.count(), output.total_spikes());
+
+    // delays of 1 are the same as no delays
+    let mut ones = nn.clone();
+    for layer in 0..ones.layers.len() {
+        let from = ones.layers[layer.saturating_sub(1)].num_neurons();
+        let to = ones.layers[layer].num_neurons();
+        ones.use_delays(layer, DMatrix::from_element(from, to, 1))
+            .unwrap();
+    }
+    for mode in [
+        SimulationMode::Sequential,
+        SimulationMode::EventDriven,
+        SimulationMode::ClockDriven { dt: 1 },
+    ] {
+        assert_eq!(
+            ones.solve(input.clone(), mode),
+            nn.solve(input.clone(), mode),
+            "{:?}",
+            mode
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use spiking_nn_resilience::snn::dataset::Dataset;
+use spiking_nn_resilience::snn::drift::{Drift, DriftModel, DriftSweep, DriftTarget};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+
+fn spike_count(output: &[(u128, Vec<u128>)]) -> usize {
+    output.iter().map(|(_, spikes)| spikes.len()).sum()
+}
+
+#[test]
+fn test_drift_models() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let right_output = nn.clone().solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+    let all = vec![DriftTarget::VTh, DriftTarget::Tau, DriftTarget::Weights];
+
+    for model in [DriftModel::Gaussian { sigma: 0.0 }, DriftModel::Linear { rate: 0.0 }] {
+        let mut drifted = nn.clone();
+        drifted.use_drift(&Drift::new(model, all.clone()));
+        assert_eq!(drifted.solve_multiple_vec_spike(input.clone()).lock().unwrap().clone(), right_output);
+    }
+
+    // the thresholds grow over time, so the neurons fire less
+    let mut drifted = nn.clone();
+    drifted.use_drift(&Drift::new(DriftModel::Linear { rate: 0.2 }, vec![DriftTarget::VTh]));
+    let output = drifted.solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+    assert!(spike_count(&output) < spike_count(&right_output));
+
+    // the weights fade out over time
+    let mut drifted = nn;
+    drifted.use_drift(&Drift::new(DriftModel::Linear { rate: -0.1 }, vec![DriftTarget::Weights]));
+    let output = drifted.solve_multiple_vec_spike(input).lock().unwrap().clone();
+    assert!(spike_count(&output) < spike_count(&right_output));
+}
+
+#[test]
+fn test_drift_sweep() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let dataset = Dataset::read_from_dir("./tests/dataset").unwrap();
+
+    let report = DriftSweep::new(
+        DriftModel::Gaussian { sigma: 0.0 },
+        vec![DriftTarget::VTh, DriftTarget::Weights],
+        vec![0.0, 0.1, 1.0],
+    )
+    .with_trials(5)
+    .run(nn, &dataset);
+    assert_eq!(report.points.len(), 3);
+    assert_eq!(report.points[0].accuracy, 1.0);
+    assert_eq!(report.points[0].same_output, 1.0);
+    assert!(report.points.iter().all(|p| (0.0..=1.0).contains(&p.accuracy)));
+    assert!(report.points[2].same_output < 1.0);
+}
+
+#[test]
+fn test_drift_sweep_seed() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let dataset = Dataset::read_from_dir("./tests/dataset").unwrap();
+    let sweep = DriftSweep::new(
+        DriftModel::Gaussian { sigma: 0.0 },
+        vec![DriftTarget::VTh, DriftTarget::Weights],
+        vec![0.1, 0.5],
+    )
+    .with_trials(3)
+    .with_seed(7);
+
+    let first = sweep.run(nn.clone(), &dataset);
+    let second = sweep.run(nn.clone(), &dataset);
+    assert_eq!(first.seed, Some(7));
+    assert_eq!(first.points, second.points);
+
+    // a linear drift is deterministic, so it has no seed
+    let report = DriftSweep::new(DriftModel::Linear { rate: 0.0 }, vec![DriftTarget::VTh], vec![0.0, 0.1])
+        .with_seed(7)
+        .run(nn, &dataset);
+    assert_eq!(report.seed, None);
+    assert_eq!(report.points[0].same_output, 1.0);
+}
@@ -0,0 +1,83 @@
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::ecc::{codeword_bits, EccOutcome, EccStatus, EccWord, CODEWORD_BITS};
+use spiking_nn_resilience::snn::model::precision::Precision;
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::Resilience;
+
+#[test]
+fn test_single_and_double_errors() {
+    let value: f64 = -65.25;
+
+    for i in 0..CODEWORD_BITS {
+        let mut word = EccWord::encode(&value);
+        word.inject(&Stuck::Transient, i);
+        let (read, status): (f64, EccStatus) = word.decode();
+        assert_eq!(read, value);
+        assert_eq!(status, EccStatus::Corrected);
+
+        for j in (i + 1)..CODEWORD_BITS {
+            let mut word = EccWord::encode(&value);
+            word.inject(&Stuck::Transient, i);
+            word.inject(&Stuck::Transient, j);
+            let (_, status): (f64, EccStatus) = word.decode();
+            assert_eq!(status, EccStatus::Detected);
+        }
+    }
+}
+
+#[test]
+fn test_narrow_words() {
+    assert_eq!(codeword_bits(64), CODEWORD_BITS);
+    assert_eq!(codeword_bits(32), 39);
+    assert_eq!(codeword_bits(16), 22);
+
+    // a half precision word is protected on its own 16 bits
+    let data = Precision::F16.to_word(-2.5);
+    assert_eq!(Precision::F16.from_word(data), -2.5);
+    for i in 0..codeword_bits(16) {
+        let mut word = EccWord::encode_word(data, 16);
+        assert_eq!(word.num_bits(), 22);
+        word.inject(&Stuck::Transient, i);
+        assert_eq!(word.decode_word(), (data, EccStatus::Corrected));
+    }
+    let (read, outcome) = EccWord::inject_random_word(data, 16, &Stuck::Transient, 1);
+    assert_eq!((read, outcome), (data, EccOutcome::Corrected));
+}
+
+#[test]
+fn test_ecc_resilience() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    );
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    // every single bit fault is corrected, so the output never changes
+    let configuration = Resilience::new(
+        vec!["neurons".to_string(), "weights".to_string()],
+        Stuck::Transient,
+        50,
+    )
+    .with_ecc(1);
+    let nn = nn.unwrap();
+    let report = configuration.clone().run_resilience_test(nn.clone(), input.clone());
+
+    let ecc = report.ecc.unwrap();
+    assert_eq!(ecc.corrected, 50);
+    assert_eq!(report.right_outputs, 50);
+
+    // the words of a reduced precision network are protected too
+    let mut f16_nn = nn.clone();
+    f16_nn.use_precision(Precision::F16);
+    let report = configuration.run_resilience_test(f16_nn, input.clone());
+    assert_eq!(report.ecc.unwrap().corrected, 50);
+    assert_eq!(report.right_outputs, 50);
+
+    // the faults of a fault list do not go through the ECC
+    let report = configuration
+        .with_faults(vec![Fault::new(0, 0, FaultComponent::VTh, 63, Stuck::One)])
+        .run_resilience_test(nn, input);
+    assert!(report.to_string().contains("ECC: not applied"));
+}
@@ -0,0 +1,109 @@
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent, FaultDuration};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::lif::ResetFault;
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::Resilience;
+
+#[test]
+fn test_read_fault_list() {
+    let csv = "layer,neuron,component,bit,stuck,time,from,link\n\
+               0,1,v_th,62,one,0,,\n\
+               1,0,weight,52,transient,10,2,\n\
+               \n\
+               1,2,full adder,30,zero,5,,3\n";
+    let faults = Fault::from_csv(csv).unwrap();
+    assert_eq!(faults.len(), 3);
+    assert_eq!(faults[0].component, FaultComponent::VTh);
+    assert_eq!((faults[1].from, faults[1].time, faults[1].stuck.clone()), (Some(2), 10, Stuck::Transient));
+    assert_eq!(faults[2].link, Some(3));
+
+    let json = r#"[{"layer": 0, "neuron": 0, "component": "reset twice", "bit": 0, "stuck": "zero"}]"#;
+    let faults = Fault::from_json(json).unwrap();
+    assert_eq!(faults[0].component, FaultComponent::Reset(ResetFault::AppliedTwice));
+    assert_eq!(faults[0].time, 0);
+
+    assert!(Fault::from_csv("layer,neuron,component,bit,stuck\n0,0,weight,1,one").is_err());
+    assert!(Fault::from_csv("layer,neuron,component,bit,stuck\n0,0,v_th,1,sometimes").is_err());
+}
+
+#[test]
+fn test_fault_list_campaign() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    let faults = vec![
+        // a huge threshold from the start
+        Fault::new(0, 0, FaultComponent::VTh, 62, Stuck::One),
+        // the same fault, active after the end of the input
+        Fault::new(0, 0, FaultComponent::VTh, 62, Stuck::One).at(1000),
+        Fault::new(0, 1, FaultComponent::Weight, 10, Stuck::Transient).from(1),
+        // a neuron that does not exist
+        Fault::new(0, 100, FaultComponent::VMem, 3, Stuck::Zero),
+    ];
+    let report = Resilience::new(vec![], Stuck::Zero, 0)
+        .with_faults(faults)
+        .run_resilience_test(nn, input);
+
+    let outcomes = report.fault_outcomes.unwrap();
+    assert_eq!(report.times, 3);
+    assert_eq!(outcomes.len(), 3);
+    assert!(outcomes[1].1);
+}
+
+#[test]
+fn test_transient_faults() {
+    let csv = "layer,neuron,component,bit,stuck,duration\n0,1,v_th,62,one,transient\n0,1,v_th,62,one,\n";
+    let faults = Fault::from_csv(csv).unwrap();
+    assert_eq!(faults[0].duration, FaultDuration::Transient);
+    assert_eq!(faults[1].duration, FaultDuration::Permanent);
+
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let right_output = nn.clone().solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+
+    // a negative threshold makes the output neuron fire at every step, only once if transient
+    let fault = Fault::new(3, 0, FaultComponent::VTh, 63, Stuck::One);
+    let mut permanent_nn = nn.clone();
+    permanent_nn.schedule_fault(fault.clone()).unwrap();
+    let permanent = permanent_nn.solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+    let mut transient_nn = nn.clone();
+    transient_nn.schedule_fault(fault.clone().at(12).lasting(FaultDuration::Transient)).unwrap();
+    let transient = transient_nn.solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+    assert!(permanent[0].1.len() > transient[0].1.len());
+    assert!(transient[0].1.len() <= right_output[0].1.len() + 1);
+
+    let report = Resilience::new(vec![], Stuck::One, 0)
+        .with_faults(vec![fault, Fault::new(0, 0, FaultComponent::VTh, 0, Stuck::Zero)])
+        .run_duration_comparison(nn.clone(), input.clone());
+    assert_eq!(report.outcomes.len(), 2);
+    assert!(report.outcomes[0].permanent_failed);
+    assert_eq!(report.outcomes[0].transient.duration, FaultDuration::Transient);
+    assert_eq!(report.both_masked(), 1);
+
+    let report = Resilience::new(vec!["neurons".to_string(), "vmem".to_string()], Stuck::Transient, 5)
+        .run_duration_comparison(nn.clone(), input.clone());
+    assert_eq!(report.outcomes.len(), 5);
+    assert!(report.outcomes.iter().all(|o| o.transient.time > 0));
+
+    // the permanent and the transient fault hit the same link of the heap
+    let adder = Fault::new(1, 0, FaultComponent::FullAdder, 62, Stuck::One);
+    let report = Resilience::new(vec!["adder".to_string(), "multiplier".to_string()], Stuck::One, 20)
+        .run_duration_comparison(nn.clone(), input.clone());
+    let listed = Resilience::new(vec![], Stuck::One, 0)
+        .with_faults(vec![adder])
+        .run_duration_comparison(nn, input);
+    for o in report.outcomes.iter().chain(&listed.outcomes) {
+        assert!(o.permanent.link.is_some());
+        assert_eq!(o.permanent.link, o.transient.link);
+    }
+}
@@ -0,0 +1,83 @@
+use spiking_nn_resilience::lif::{ComparatorInjection, ComparatorOperand};
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::fixed::{FixedLif, FixedNeuron, Overflow, QFormat};
+use spiking_nn_resilience::snn::model::{apply_stuck, Stuck};
+use spiking_nn_resilience::{Model, Resilience};
+
+#[test]
+fn test_overflow() {
+    let saturating = QFormat::new(3, 4, Overflow::Saturating).unwrap();
+    let wrapping = QFormat::new(3, 4, Overflow::Wrapping).unwrap();
+
+    assert_eq!(saturating.width(), 8);
+    assert_eq!(saturating.quantize(1.5), 24);
+    assert_eq!(saturating.quantize(100.0), 127);
+    assert_eq!(saturating.quantize(-100.0), -128);
+    assert_eq!(wrapping.normalize(128), -128);
+    assert!(QFormat::new(20, 20, Overflow::Wrapping).is_err());
+
+    // the sign bit of a Q3.4 word is the bit 7 of an i8
+    assert_eq!(saturating.stuck_word(24, &Stuck::One, 7), apply_stuck(24i8, &Stuck::One, 7) as i32);
+    assert_eq!(saturating.stuck_word(24, &Stuck::One, 7), 24 - 128);
+
+    // in a Q4.7 word (12 bits) the sign bit is the bit 11
+    let q4_7 = QFormat::new(4, 7, Overflow::Wrapping).unwrap();
+    assert_eq!(q4_7.stuck_word(1, &Stuck::Transient, 11), 1 - 2048);
+}
+
+#[test]
+fn test_fixed_comparator() {
+    let format = QFormat::new(3, 4, Overflow::Saturating).unwrap();
+
+    // the sign bit of the threshold stuck at one: the word is a negative threshold, not a large one
+    let mut neuron = FixedNeuron::new(0.0, 0.0, 1.0, 1.0, format);
+    neuron.comparator = Some(ComparatorInjection::new(Stuck::One, ComparatorOperand::VTh, 7));
+    assert_eq!(FixedLif::handle_spike(&mut neuron, 0.5, 1), 1.0);
+    assert_eq!(neuron.v_th, 16);
+}
+
+#[test]
+fn test_fixed_network() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    let format = QFormat::new(7, 16, Overflow::Saturating).unwrap();
+    let fixed_nn = FixedLif::from_lif_network(&nn, format).unwrap();
+
+    let right_output = nn.clone().solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+    let res = fixed_nn.clone().solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+    assert_eq!(res, right_output);
+
+    let report = Resilience::new(
+        vec!["neurons".to_string(), "weights".to_string(), "vmem".to_string()],
+        Stuck::Transient,
+        20,
+    )
+    .run_resilience_test(fixed_nn.clone(), input.clone());
+    assert_eq!(report.right_output, right_output);
+    assert_eq!(report.times, 20);
+    assert!(report.right_outputs <= 20);
+
+    // Q7.16: the sign bit is the bit 23 of the word
+    let faults = vec![
+        // a negative threshold makes the output neuron fire at every step
+        Fault::new(3, 0, FaultComponent::VTh, 23, Stuck::One),
+        // the least significant bit of a threshold is masked
+        Fault::new(3, 0, FaultComponent::VTh, 0, Stuck::One),
+        // the sign bit of the output of the adder stuck at one: the neuron never fires
+        Fault::new(3, 1, FaultComponent::FullAdder, 23, Stuck::One),
+    ];
+    let report = Resilience::new(vec![], Stuck::One, 0)
+        .with_faults(faults)
+        .run_resilience_test(fixed_nn, input);
+    // true if the output is the fault free one
+    let right: Vec<bool> = report.fault_outcomes.unwrap().iter().map(|(_, right)| *right).collect();
+    assert_eq!(right, vec![false, true, false]);
+    assert_eq!(report.right_outputs, 1);
+}
@@ -1,5 +1,6 @@
 
-use spiking_nn_resilience::snn::model::{heap::HeapCalculator, Stuck};
+use spiking_nn_resilience::snn::model::heap::{adder_links, num_links, HeapCalculator};
+use spiking_nn_resilience::snn::model::Stuck;
 
 
 #[test]
@@ -26,4 +27,59 @@ fn bit(){
     let changed_bit = (64 - xor_result.leading_zeros()) - 1;
 
     println!("Bit da cambiare: {}", changed_bit);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_product() {
+    let set_bit = |val: f64, i: u32| f64::from_bits(val.to_bits() | (1u64 << i));
+
+    let mut corrupted = 0;
+    for _ in 0..100 {
+        let mut multiplier: HeapCalculator<f64, u64> = HeapCalculator::multiplier(Stuck::One);
+        let res = multiplier.multiply_all(&[3.0, 0.5]);
+        // one of the operands or the product has a bit stuck at one
+        assert!(
+            (0..64).any(|i| [set_bit(3.0, i) * 0.5, 3.0 * set_bit(0.5, i), set_bit(1.5, i)]
+                .iter()
+                .any(|faulty| faulty.to_bits() == res.to_bits())),
+            "unexpected product: {}",
+            res
+        );
+        if res.to_bits() != 1.5f64.to_bits() {
+            corrupted += 1;
+        }
+    }
+    assert!(corrupted > 0);
+}
+
+#[test]
+fn test_product_links() {
+    // the lowest bit is zero in the operands and in the product
+    let set_bit = |val: f64| f64::from_bits(val.to_bits() | 1);
+    let expected = [set_bit(3.0) * 0.5, 3.0 * set_bit(0.5), set_bit(1.5)];
+    for (link, expected) in expected.into_iter().enumerate() {
+        let mut multiplier: HeapCalculator<f64, u64> = HeapCalculator::multiplier_with_fault(Stuck::One, Some(link), 0).unwrap();
+        let res = multiplier.multiply_all(&[3.0, 0.5]);
+        assert_ne!(res, 1.5);
+        assert_eq!(res, expected);
+    }
+    // the links of a heap of two inputs are the operands and the product
+    assert_eq!(num_links(2), 3);
+    assert!(HeapCalculator::<f64, u64>::multiplier_with_fault(Stuck::One, Some(3), 0).is_err());
+}
+
+#[test]
+fn test_sum_links() {
+    let inputs = [1.0, 2.0, 3.0, 4.0];
+    let sum = |link| {
+        let mut adder: HeapCalculator<f64, u64> = HeapCalculator::with_fault(4, Stuck::One, Some(link), 0).unwrap();
+        adder.sum_all(&inputs)
+    };
+    // a fault in an input or in a partial sum reaches the output
+    assert_eq!(sum(0), f64::from_bits(1f64.to_bits() | 1) + 9.0);
+    assert_eq!(sum(4), f64::from_bits(3f64.to_bits() | 1) + 7.0);
+    // the output link and the last one are part of the fault space of the full adder, but are not read
+    assert_eq!(adder_links(4), 8);
+    assert_eq!((sum(6), sum(7)), (10.0, 10.0));
+    assert!(HeapCalculator::<f64, u64>::with_fault(4, Stuck::One, Some(8), 0).is_err());
+}
@@ -0,0 +1,68 @@
+use spiking_nn_resilience::snn::input_fault::{InputComponent, InputFault};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::Resilience;
+
+#[test]
+fn test_input_faults() {
+    let input = vec![(0, vec![1, 2, 5]), (1, vec![3, 4])];
+
+    let fault = InputFault::Timestamp { neuron: 1, spike: 0, bit: 0, stuck: Stuck::Transient };
+    assert_eq!(fault.apply(&input).unwrap()[1], (1, vec![2, 4]));
+    // the spike moved on another one is merged
+    let fault = InputFault::Timestamp { neuron: 0, spike: 2, bit: 2, stuck: Stuck::Zero };
+    assert_eq!(fault.apply(&input).unwrap()[0], (0, vec![1, 2]));
+    let fault = InputFault::Timestamp { neuron: 0, spike: 2, bit: 127, stuck: Stuck::One };
+    assert_eq!(fault.apply(&input).unwrap()[0].1[2], 5 + (1u128 << 127));
+
+    assert_eq!(InputFault::DroppedEvent { neuron: 0, spike: 1 }.apply(&input).unwrap()[0], (0, vec![1, 5]));
+    assert_eq!(InputFault::StuckOff { neuron: 0 }.apply(&input).unwrap()[0], (0, vec![]));
+    assert_eq!(InputFault::StuckOn { neuron: 1 }.apply(&input).unwrap()[1], (1, vec![1, 2, 3, 4, 5]));
+
+    assert!(InputFault::DroppedEvent { neuron: 0, spike: 3 }.apply(&input).is_err());
+    assert!(InputFault::StuckOff { neuron: 2 }.apply(&input).is_err());
+
+    for component in InputComponent::all() {
+        let fault = InputFault::random(component, &input, &Stuck::Zero).unwrap();
+        assert_eq!(fault.component(), component);
+        assert!(fault.apply(&input).is_ok());
+    }
+    assert!(InputFault::random(InputComponent::Timestamp, &[(0, vec![])], &Stuck::Zero).is_none());
+}
+
+#[test]
+fn test_input_campaign() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    // silent and corrupted inputs must not stop the simulation
+    let silent = vec![(0, vec![]), (1, vec![]), (2, vec![])];
+    let res = nn.clone().solve_multiple_vec_spike(silent).lock().unwrap().clone();
+    assert!(res.iter().all(|(_, spikes)| spikes.is_empty()));
+    // until the moved spike arrives the network runs as without it, then it crosses the layers
+    let far = InputFault::Timestamp { neuron: 0, spike: 9, bit: 127, stuck: Stuck::One }.apply(&input).unwrap();
+    let dropped = InputFault::DroppedEvent { neuron: 0, spike: 9 }.apply(&input).unwrap();
+    let res = nn.clone().solve_multiple_vec_spike(far).lock().unwrap().clone();
+    let expected = nn.clone().solve_multiple_vec_spike(dropped).lock().unwrap().clone();
+    let (before, after): (Vec<u128>, Vec<u128>) = res
+        .iter()
+        .flat_map(|(_, spikes)| spikes.iter().copied())
+        .partition(|ts| *ts < 1 << 127);
+    assert_eq!(before.len(), expected.iter().map(|(_, spikes)| spikes.len()).sum::<usize>());
+    for ((_, spikes), (_, expected)) in res.iter().zip(expected.iter()) {
+        assert!(spikes.starts_with(expected));
+    }
+    assert_eq!(after, vec![(1 << 127) + 19]);
+
+    // every input neuron drives some output spike, so a stuck-off neuron always changes the output
+    let report = Resilience::new(vec!["stuck off".to_string()], Stuck::Transient, 30)
+        .run_resilience_test(nn.clone(), input.clone());
+    assert_eq!((report.times, report.right_outputs), (30, 0));
+    let report = Resilience::new(vec!["input".to_string()], Stuck::Transient, 30).run_resilience_test(nn, input);
+    assert_eq!(report.times, 30);
+}
@@ -0,0 +1,43 @@
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::nn::SimulationMode;
+use spiking_nn_resilience::snn::output::SimulationOutput;
+
+#[test]
+fn test_simulation_output() {
+    let output = SimulationOutput::from(vec![(0, vec![2, 4, 7]), (1, vec![]), (2, vec![5])]);
+    assert_eq!(output.num_neurons(), 3);
+    assert_eq!(output.spikes(0), &[2, 4, 7]);
+    assert!(output.spikes(5).is_empty());
+    assert_eq!((output.spike_count(2), output.total_spikes()), (1, 4));
+    assert!(!output.is_silent() && SimulationOutput::new(2).is_silent());
+    let firing: Vec<u128> = output.iter().filter(|(_, spikes)| !spikes.is_empty()).map(|(n, _)| *n).collect();
+    assert_eq!(firing, vec![0, 2]);
+    assert_eq!((&output).into_iter().count(), 3);
+
+    let json = serde_json::to_string(&output).unwrap();
+    assert_eq!(
+        json,
+        r#"[{"neuron":0,"spikes":[2,4,7]},{"neuron":1,"spikes":[]},{"neuron":2,"spikes":[5]}]"#
+    );
+    // the same format of the input spikes
+    assert_eq!(InputJson::parse_input(&json).unwrap(), output.as_slice());
+    assert_eq!(serde_json::from_str::<SimulationOutput>(&json).unwrap(), output);
+    assert_eq!(output.clone().into_vec(), vec![(0, vec![2, 4, 7]), (1, vec![]), (2, vec![5])]);
+}
+
+#[test]
+fn test_solve_output() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    let output = nn.solve_threaded(input.clone());
+    let shared_output = nn.clone().solve_multiple_vec_spike(input.clone());
+    assert_eq!(output, *shared_output.lock().unwrap());
+    assert_eq!(output, nn.solve(input, SimulationMode::Sequential));
+    assert_eq!(output.num_neurons(), nn.layers.last().unwrap().num_neurons());
+}
@@ -0,0 +1,82 @@
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::precision::{Bf16, Precision, PrecisionHeap, F16};
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::snn::sampling::{Allocation, Sampling, StratifyBy};
+use spiking_nn_resilience::Resilience;
+
+#[test]
+fn test_conversions() {
+    // values exactly representable in both formats
+    for val in [0.0, 1.0, -2.5, 0.375, 1024.0] {
+        assert_eq!(F16::from_f64(val).to_f64(), val);
+        assert_eq!(Bf16::from_f64(val).to_f64(), val);
+    }
+    // rounding to nearest even, overflow and subnormals
+    assert_eq!(F16::from_f64(2049.0).to_f64(), 2048.0);
+    assert_eq!(F16::from_f64(2051.0).to_f64(), 2052.0);
+    assert_eq!(F16::from_f64(1e6).to_f64(), f64::INFINITY);
+    assert_eq!(F16::from_f64(2f64.powi(-24)).to_f64(), 2f64.powi(-24));
+    assert_eq!(Bf16::from_f64(1.0 + 3.0 * 2f64.powi(-8)).to_f64(), 1.0 + 2f64.powi(-6));
+    assert!(F16::from_f64(f64::NAN).to_f64().is_nan());
+
+    assert_eq!(Precision::F32.round(0.1), 0.1f32 as f64);
+    assert_eq!(Precision::F16.stuck_bit(1.0, &Stuck::Transient, 15), -1.0);
+    assert_eq!(Precision::BF16.stuck_bit(1.0, &Stuck::Zero, 13), 2f64.powi(-64));
+
+    let mut heap = PrecisionHeap::new(Precision::F16, 4, Stuck::Zero);
+    let res = heap.sum_all(&[1.0, 2.0, 3.0, 4.0]);
+    assert!(res <= 10.0, "a bit stuck at zero can only decrease a positive sum: {}", res);
+}
+
+#[test]
+fn test_precision_network() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let right_output = nn.clone().solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+
+    let mut f32_nn = nn.clone();
+    f32_nn.use_precision(Precision::F32);
+    let res = f32_nn.clone().solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+    assert_eq!(res, right_output);
+
+    let mut f16_nn = nn.clone();
+    f16_nn.use_precision(Precision::F16);
+    // bit 14 is the top of the exponent of a f16, and a low bit of the mantissa of a f64: the threshold of
+    // the output neuron drops in the f16 network only
+    let faults = vec![
+        Fault::new(3, 0, FaultComponent::VTh, 14, Stuck::Zero),
+        Fault::new(3, 0, FaultComponent::VTh, 62, Stuck::One),
+    ];
+    let outcomes = |nn| {
+        Resilience::new(vec![], Stuck::One, 0)
+            .with_faults(faults.clone())
+            .run_resilience_test(nn, input.clone())
+            .fault_outcomes
+            .unwrap()
+            .into_iter()
+            .map(|(fault, right)| (fault.bit, right))
+            .collect::<Vec<_>>()
+    };
+    // the bits of a f64 missing in a f16 are skipped
+    assert_eq!(outcomes(f16_nn.clone()), vec![(14, false)]);
+    assert_eq!(outcomes(nn), vec![(14, true), (62, true)]);
+
+    let report = Resilience::new(
+        vec!["neurons".to_string(), "full adder".to_string(), "multiplier".to_string()],
+        Stuck::Transient,
+        20,
+    )
+    .with_sampling(Sampling::Stratified {
+        by: vec![StratifyBy::Component],
+        allocation: Allocation::Proportional,
+    })
+    .run_resilience_test(f16_nn, input);
+    assert_eq!(report.times, 20);
+    assert!(report.fault_outcomes.unwrap().iter().all(|(fault, _)| fault.bit < 16));
+}
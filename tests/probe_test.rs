@@ -0,0 +1,106 @@
+use nalgebra::DMatrix;
+use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::snn::nn::SimulationMode;
+use spiking_nn_resilience::snn::output::SimulationOutput;
+use spiking_nn_resilience::snn::probe::ProbeTrace;
+use spiking_nn_resilience::{Resilience, NN};
+
+#[test]
+fn test_probe_membrane_potential() {
+    // v_rest 0, v_reset 0, v_th 1.5, tau 1
+    let mut nn = NN::<LeakyIntegrateFire>::new()
+        .layer(
+            vec![LifNeuron::new(0.0, 0.0, 1.5, 1.0)],
+            DMatrix::from_vec(1, 1, vec![1.0]),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+        )
+        .unwrap();
+    nn.add_probe(0, 0).unwrap();
+    // a second probe of the same neuron is not added
+    nn.add_probe(0, 0).unwrap();
+    assert!(nn.add_probe(0, 1).is_err());
+    assert!(nn.add_probe(1, 0).is_err());
+
+    let output = nn.solve_sequential(vec![(0, vec![1, 2, 3])]);
+    assert_eq!(output.probes().len(), 1);
+    let trace = output.probe(0, 0).unwrap();
+    assert_eq!(trace.variables, vec!["v_mem", "ts_old", "v_th"]);
+    let v_mem = trace.series("v_mem").unwrap();
+    assert_eq!(
+        v_mem.iter().map(|(ts, _)| *ts).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    assert_eq!(v_mem[0].1, 1.0);
+    // the potential decays between the inputs, then it crosses the threshold and is reset
+    assert!(v_mem[1].1 > 1.0 && v_mem[1].1 < 1.5);
+    assert_eq!(v_mem[2].1, 0.0);
+    assert_eq!(trace.fired(), vec![false, false, true]);
+    assert_eq!(output.spikes(0), &[4]);
+    assert!(trace.series("v_mem_raw").is_none());
+    assert!(output.probe(0, 1).is_none());
+}
+
+#[test]
+fn test_probe_engines() {
+    let mut nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    nn.add_probe(0, 2).unwrap();
+    nn.add_probe(1, 0).unwrap();
+
+    let threaded = nn.solve_threaded(input.clone());
+    let sequential = nn.solve(input, SimulationMode::Sequential);
+    for (layer, neuron) in [(0, 2), (1, 0)] {
+        let trace = sequential.probe(layer, neuron).unwrap();
+        assert!(!trace.samples.is_empty());
+        assert_eq!(threaded.probe(layer, neuron), Some(trace));
+    }
+    // the probes do not change the output of the network
+    assert_eq!(
+        threaded.num_neurons(),
+        nn.layers.last().unwrap().num_neurons()
+    );
+    assert_eq!(threaded, sequential);
+
+    // a trace can be serialized on its own
+    let trace = sequential.probe(1, 0).unwrap();
+    let json = serde_json::to_string(trace).unwrap();
+    assert_eq!(&serde_json::from_str::<ProbeTrace>(&json).unwrap(), trace);
+}
+
+#[test]
+fn test_probe_campaign() {
+    let mut nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    nn.add_probe(0, 2).unwrap();
+
+    // the fault changes the probed membrane potential, not the spikes
+    let fault = Fault::new(0, 2, FaultComponent::VMem, 0, Stuck::One);
+    let mut faulty = nn.clone();
+    faulty.schedule_fault(fault.clone()).unwrap();
+    let right = nn.solve(input.clone(), SimulationMode::Sequential);
+    let output = faulty.solve(input.clone(), SimulationMode::Sequential);
+    assert_ne!(output.probe(0, 2), right.probe(0, 2));
+    assert_eq!(output, right);
+
+    let report = Resilience::new(vec![], Stuck::One, 0)
+        .with_faults(vec![fault])
+        .run_resilience_test(nn, input);
+    assert_eq!((report.times, report.right_outputs), (1, 1));
+
+    // the round trip through JSON drops the traces only
+    let json = serde_json::to_string(&right).unwrap();
+    assert_eq!(serde_json::from_str::<SimulationOutput>(&json).unwrap(), right);
+}
@@ -0,0 +1,65 @@
+use nalgebra::DMatrix;
+use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::quantization::{QuantParams, QuantizationReport, QuantizationScheme, QuantizedNN};
+use spiking_nn_resilience::NN;
+
+#[test]
+fn test_asymmetric() {
+    let params = QuantParams::from_range(-1.0, 3.0, 8, QuantizationScheme::Asymmetric).unwrap();
+    assert_eq!(params.min_int(), 0);
+    assert_eq!(params.max_int(), 255);
+    assert_eq!(params.dequantize(params.quantize(0.0)), 0.0);
+    assert_eq!(params.quantize(-1.0), 0);
+    assert_eq!(params.quantize(100.0), 255);
+    assert!(QuantParams::from_range(0.0, f64::INFINITY, 8, QuantizationScheme::Symmetric).is_err());
+
+    let report = QuantizationReport::new(
+        vec![(0, vec![1, 2]), (1, vec![3])].into(),
+        vec![(0, vec![1]), (1, vec![3, 4])].into(),
+    );
+    assert_eq!((report.missing_spikes, report.extra_spikes, report.diverging_neurons), (1, 1, 2));
+    assert_eq!(report.divergence(), 2.0 / 6.0);
+
+    // two spikes of a neuron at the same time are two spikes
+    let report = QuantizationReport::new(vec![(0, vec![1, 1, 2])].into(), vec![(0, vec![1, 2])].into());
+    assert_eq!((report.missing_spikes, report.extra_spikes, report.diverging_neurons), (1, 0, 1));
+    assert!(!report.is_equivalent());
+}
+
+#[test]
+fn test_quantized_network() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    for scheme in [QuantizationScheme::Symmetric, QuantizationScheme::Asymmetric] {
+        let quantized = QuantizedNN::new(&nn, 16, scheme).unwrap();
+        assert_eq!(quantized.get_layer(0).unwrap().input_weights().ncols(), nn.layers[0].num_neurons());
+        let report = quantized.compare(&nn, input.clone());
+        assert!(report.is_equivalent(), "{}", report);
+    }
+
+    // with 2 bits the smallest weights are lost: 27 spikes of the float network become 14
+    let report = QuantizedNN::new(&nn, 2, QuantizationScheme::Symmetric).unwrap().compare(&nn, input);
+    assert_eq!((report.missing_spikes, report.extra_spikes, report.diverging_neurons), (13, 0, 2));
+    assert_eq!(report.divergence(), 13.0 / 41.0);
+
+    // a value out of the range would be lost silently
+    for weight in [f64::NAN, f64::NEG_INFINITY] {
+        let broken = NN::<LeakyIntegrateFire>::new()
+            .layer(
+                vec![LifNeuron::new(0.0, 0.0, 1.0, 1.0)],
+                DMatrix::from_vec(1, 1, vec![weight]),
+                DMatrix::from_vec(1, 1, vec![0.0]),
+            )
+            .unwrap();
+        for scheme in [QuantizationScheme::Symmetric, QuantizationScheme::Asymmetric] {
+            assert!(QuantizedNN::new(&broken, 8, scheme).is_err());
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::snn::nn::SimulationMode;
+use spiking_nn_resilience::snn::recorder::SpikeRecorder;
+
+#[test]
+fn test_record_all_layers() {
+    let mut nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    nn.use_recorder(SpikeRecorder::new());
+
+    let output = nn.solve(input.clone(), SimulationMode::Threaded);
+    let recorder = output.recording().unwrap();
+    let last = nn.layers.len() - 1;
+    assert_eq!(recorder.layer(last).unwrap(), output);
+    for layer in 0..last {
+        let trains = recorder.layer(layer).unwrap();
+        assert_eq!(trains.num_neurons(), nn.layers[layer].num_neurons());
+        assert!(!trains.is_silent());
+    }
+    assert!(recorder.layer(last + 1).is_none());
+    let spikes = recorder.spikes();
+    assert!(spikes.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    // every engine records the same spikes, and every simulation its own ones
+    let sequential = nn.solve(input, SimulationMode::Sequential);
+    assert_eq!(sequential.recording().unwrap().spikes(), spikes);
+}
+
+#[test]
+fn test_record_fault_propagation() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let record = |fault: Option<Fault>| {
+        let mut nn = nn.clone();
+        if let Some(fault) = fault {
+            nn.schedule_fault(fault).unwrap();
+        }
+        nn.use_recorder(SpikeRecorder::with_layers(vec![0, 1]));
+        nn.solve_sequential(input.clone()).recording().cloned().unwrap()
+    };
+
+    // a fault in the second layer leaves the first one untouched
+    let fault_free = record(None);
+    let faulty = record(Some(Fault::new(1, 1, FaultComponent::VTh, 63, Stuck::One)));
+    assert_eq!(faulty.layer(0), fault_free.layer(0));
+    assert_ne!(faulty.layer(1), fault_free.layer(1));
+    assert!(faulty.layer(2).is_none());
+    assert!(faulty.spikes().iter().all(|spike| spike.layer_id < 2));
+}
@@ -0,0 +1,94 @@
+use spiking_nn_resilience::snn::fault::FaultComponent;
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson, SamplingJson};
+use spiking_nn_resilience::snn::model::precision::{BitField, Precision};
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::snn::sampling::{Allocation, ImportanceWeights, Sampling, StratifyBy};
+use spiking_nn_resilience::Resilience;
+
+#[test]
+fn test_sample_plan() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let components = vec!["neurons".to_string(), "weights".to_string()];
+
+    let stratified = Sampling::Stratified {
+        by: vec![StratifyBy::Layer],
+        allocation: Allocation::Equal,
+    };
+    let plan = stratified.plan(&nn, &components, &Stuck::One, 40).unwrap().unwrap();
+    assert_eq!(plan.faults().len(), 40);
+    for layer in 0..nn.get_num_layers() {
+        assert_eq!(plan.faults().iter().filter(|f| f.layer == layer).count(), 40 / nn.get_num_layers());
+    }
+
+    // the faults out of the exponent are almost never drawn
+    let importance = Sampling::Importance(
+        ImportanceWeights::default()
+            .with_bit_field(BitField::Mantissa, 1e-12)
+            .and_then(|weights| weights.with_bit_field(BitField::Sign, 1e-12))
+            .unwrap(),
+    );
+    let plan = importance.plan(&nn, &components, &Stuck::One, 40).unwrap().unwrap();
+    let layout = Precision::F64.layout();
+    assert!(plan.faults().iter().all(|f| layout.field(f.bit) == BitField::Exponent));
+
+    // every stratum needs two faults to estimate its variance
+    assert!(stratified.plan(&nn, &components, &Stuck::One, 2 * nn.get_num_layers() as u128 - 1).is_err());
+    let single = Sampling::Stratified {
+        by: vec![],
+        allocation: Allocation::Proportional,
+    };
+    let plan = single.plan(&nn, &components, &Stuck::One, 10).unwrap().unwrap();
+    let failures: Vec<bool> = (0..10).map(|i| i % 2 == 0).collect();
+    let estimate = plan.estimate(&failures);
+    assert_eq!(estimate.failure_rate, 0.5);
+    assert!((estimate.std_error - (0.25f64 / 9.0).sqrt()).abs() < 1e-12);
+    let plan = single.plan(&nn, &components, &Stuck::One, 1).unwrap().unwrap();
+    assert!(plan.estimate(&[true]).std_error.is_infinite());
+    assert!(plan.estimate(&[true]).to_string().contains("unknown"));
+
+    assert!(Sampling::Uniform.plan(&nn, &components, &Stuck::One, 40).unwrap().is_none());
+    assert!(stratified.plan(&nn, &["nothing".to_string()], &Stuck::One, 40).is_err());
+    // the input encoding is not part of the fault space of the network
+    assert!(stratified.plan(&nn, &["input".to_string()], &Stuck::One, 40).is_err());
+
+    // a fault with no chance to be drawn would be missing from the estimate
+    for weight in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(ImportanceWeights::default().with_bit_field(BitField::Sign, weight).is_err());
+        assert!(ImportanceWeights::default().with_component(FaultComponent::VTh, weight).is_err());
+    }
+    let json = r#"{"method": "importance", "weights": {"exponent": 10, "sign": -1}}"#;
+    assert!(serde_json::from_str::<SamplingJson>(json).unwrap().to_sampling().is_err());
+}
+
+#[test]
+fn test_sampled_campaign() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    let samplings = [
+        Sampling::Stratified {
+            by: vec![StratifyBy::Component, StratifyBy::BitField],
+            allocation: Allocation::Proportional,
+        },
+        Sampling::Importance(ImportanceWeights::default().with_bit_field(BitField::Exponent, 20.0).unwrap()),
+    ];
+    for sampling in samplings {
+        let report = Resilience::new(vec!["neurons".to_string(), "vmem".to_string()], Stuck::Transient, 30)
+            .with_sampling(sampling)
+            .run_resilience_test(nn.clone(), input.clone());
+        let (low, high) = report.estimate.confidence_interval();
+        assert!(report.estimate.failure_rate >= 0.0 && report.estimate.std_error >= 0.0);
+        assert!(0.0 <= low && low <= high && high <= 1.0);
+        assert_eq!(report.fault_outcomes.unwrap().len() as u128, report.times);
+    }
+}
@@ -0,0 +1,92 @@
+use spiking_nn_resilience::lif::LeakyIntegrateFire;
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::session::Session;
+
+#[test]
+fn test_session_segments() {
+    let mut nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    nn.add_probe(1, 0).unwrap();
+    let expected = nn.solve_sequential(input.clone());
+
+    // the whole input at once
+    let mut session = nn.session();
+    session.feed(input.clone());
+    assert_eq!(session.run(), expected);
+    assert!(session.is_idle());
+
+    // the input in two segments, split at time `split`
+    session.reset();
+    assert_eq!(session.time(), 0);
+    let split = 5;
+    let (first, second): (Vec<_>, Vec<_>) = input
+        .iter()
+        .map(|(n, spikes)| {
+            let (before, after): (Vec<u128>, Vec<u128>) =
+                spikes.iter().partition(|ts| **ts <= split);
+            ((*n, before), (*n, after))
+        })
+        .unzip();
+    session.feed(first);
+    // the spikes fired up to `split` by the hidden layers stay pending
+    let mut output = session.run_until(split).into_vec();
+    assert!(output
+        .iter()
+        .flat_map(|(_, spikes)| spikes)
+        .all(|ts| *ts <= split + 1));
+    assert!(!session.is_idle());
+    session.feed(second);
+    let segment = session.run();
+    for (train, (_, spikes)) in output.iter_mut().zip(segment.iter()) {
+        train.1.extend(spikes);
+        train.1.sort();
+    }
+    assert_eq!(expected, output);
+    assert_eq!(
+        segment.probe(1, 0).unwrap().samples.last(),
+        expected.probe(1, 0).unwrap().samples.last()
+    );
+}
+
+#[test]
+fn test_session_step_and_reset() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    let mut session = nn.session();
+    assert!(session.step().is_none());
+    session.feed(input.clone());
+    let mut steps = 0;
+    let mut spikes = 0;
+    while let Some(output) = session.step() {
+        assert!(output.iter().all(|spike| spike.ts == session.time()));
+        steps += 1;
+        spikes += output.len();
+    }
+    assert!(steps > 0);
+    assert_eq!(spikes, nn.solve_sequential(input.clone()).total_spikes());
+
+    // reset restores the initial state, and the same input brings the neurons to the same state
+    let state = |session: &Session<LeakyIntegrateFire>| -> Vec<f64> {
+        session.layers()[0]
+            .iter_neurons()
+            .map(|neuron| neuron.v_mem)
+            .collect()
+    };
+    let after_run = state(&session);
+    session.reset();
+    assert_ne!(state(&session), after_run);
+    session.feed(input.clone());
+    session.run();
+    assert_eq!(state(&session), after_run);
+}
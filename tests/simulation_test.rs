@@ -0,0 +1,148 @@
+use spiking_nn_resilience::snn::fault::{Fault, FaultComponent, FaultDuration};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::model::precision::Precision;
+use spiking_nn_resilience::snn::model::Stuck;
+use spiking_nn_resilience::snn::nn::{SimulationMode, MAX_CLOCK_TICKS};
+use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+use spiking_nn_resilience::{Resilience, NN};
+use nalgebra::DMatrix;
+
+#[test]
+fn test_sequential_same_as_threaded() {
+    let networks = [
+        ("./tests/layers.json", "./tests/weights.json", "./tests/configurations.json", "./tests/input_spikes.json"),
+        ("./tests/layers3.json", "./tests/weights3.json", "./tests/configurations3.json", "./tests/input_spikes3.json"),
+    ];
+    for (layers, weights, configurations, input) in networks {
+        let nn = NeuronJson::read_from_file(layers, weights, configurations).unwrap();
+        let input = InputJson::read_input_from_file(input);
+        let threaded = nn.clone().solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+        assert_eq!(nn.solve_sequential(input.clone()), threaded);
+
+        let mut f32_nn = nn.clone();
+        f32_nn.use_precision(Precision::F32);
+        assert_eq!(
+            f32_nn.clone().solve(input.clone(), SimulationMode::Sequential),
+            f32_nn.clone().solve(input.clone(), SimulationMode::Threaded)
+        );
+        assert_eq!(nn.solve_sequential(vec![(0, vec![])]).num_neurons(), threaded.len());
+    }
+
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let faults = [
+        Fault::new(3, 0, FaultComponent::VTh, 63, Stuck::One),
+        Fault::new(3, 0, FaultComponent::VTh, 63, Stuck::One).at(12).lasting(FaultDuration::Transient),
+        Fault::new(1, 1, FaultComponent::Weight, 62, Stuck::One).from(0).at(5),
+        Fault::new(0, 1, FaultComponent::VMem, 60, Stuck::Transient),
+    ];
+    for fault in faults {
+        let mut faulty = nn.clone();
+        faulty.schedule_fault(fault.clone()).unwrap();
+        assert_eq!(
+            faulty.clone().solve(input.clone(), SimulationMode::Sequential),
+            faulty.clone().solve(input.clone(), SimulationMode::Threaded),
+            "{}",
+            fault
+        );
+    }
+}
+
+#[test]
+fn test_campaign_simulation_modes() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let faults = vec![
+        Fault::new(3, 0, FaultComponent::VTh, 63, Stuck::One),
+        Fault::new(0, 0, FaultComponent::VTh, 0, Stuck::Zero),
+        Fault::new(2, 1, FaultComponent::Weight, 62, Stuck::One).from(0),
+    ];
+    let resilience = Resilience::new(vec![], Stuck::One, 0).with_faults(faults);
+
+    let sequential = resilience.run_resilience_test(nn.clone(), input.clone());
+    let threaded = resilience
+        .clone()
+        .with_simulation(SimulationMode::Threaded)
+        .run_resilience_test(nn, input);
+    assert_eq!(sequential.right_output, threaded.right_output);
+    assert_eq!(sequential.right_outputs, threaded.right_outputs);
+    let outcomes = |outcomes: Option<Vec<(Fault, bool)>>| {
+        outcomes.unwrap().into_iter().map(|(_, right)| right).collect::<Vec<_>>()
+    };
+    assert_eq!(outcomes(sequential.fault_outcomes), outcomes(threaded.fault_outcomes));
+    assert_eq!("threaded".parse::<SimulationMode>(), Ok(SimulationMode::Threaded));
+}
+
+#[test]
+fn test_event_driven() {
+    // every neuron of this network receives a spike at every input
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers3.json",
+        "./tests/weights3.json",
+        "./tests/configurations3.json",
+    )
+    .unwrap();
+    let mut input = InputJson::read_input_from_file("./tests/input_spikes3.json");
+    // the other engines split the input spikes of a time step in vectors as long as the first layer
+    input.iter_mut().for_each(|(_, spikes)| spikes.dedup());
+    assert_eq!(nn.solve_event_driven(input.clone()), nn.solve_sequential(input));
+
+    // the second neuron rests above its threshold, but it never receives a spike
+    let nn = NN::<LeakyIntegrateFire>::new()
+        .layer(
+            vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0), LifNeuron::new(1.0, 0.0, 0.5, 1.0)],
+            DMatrix::from_vec(2, 2, vec![1.0, 0.0, 0.0, 1.0]),
+            DMatrix::from_vec(2, 2, vec![0.0, 0.0, 0.0, 0.0]),
+        )
+        .unwrap();
+    let input = vec![(0, vec![1, 3]), (1, vec![])];
+    assert_eq!(nn.solve_event_driven(input.clone()), vec![(0, vec![2, 4]), (1, vec![])]);
+    assert_eq!(nn.solve_sequential(input.clone()), vec![(0, vec![2, 4]), (1, vec![2, 4])]);
+    assert_eq!("event-driven".parse::<SimulationMode>(), Ok(SimulationMode::EventDriven));
+}
+
+#[test]
+fn test_clock_driven() {
+    let layer = |v_rest: f64| {
+        NN::<LeakyIntegrateFire>::new()
+            .layer(
+                vec![LifNeuron::new(v_rest, 0.0, 0.5, 1.0)],
+                DMatrix::from_vec(1, 1, vec![1.0]),
+                DMatrix::from_vec(1, 1, vec![0.0]),
+            )
+            .unwrap()
+    };
+    let input = vec![(0, vec![1, 5])];
+
+    // resting above the threshold, the neuron fires at every tick, also without input
+    let nn = layer(1.0);
+    assert_eq!(nn.solve_sequential(input.clone()), vec![(0, vec![2, 6])]);
+    assert_eq!(nn.solve_clock_driven(input.clone(), 1), vec![(0, vec![2, 3, 4, 5, 6, 7])]);
+
+    // the input spikes are received at the next tick, the output spikes one tick later
+    let nn = layer(0.0);
+    assert_eq!(nn.solve_clock_driven(input.clone(), 1), nn.solve_sequential(input.clone()));
+    assert_eq!(nn.solve_clock_driven(input.clone(), 2), vec![(0, vec![4, 8])]);
+    assert_eq!(
+        nn.clone().solve(input, SimulationMode::ClockDriven { dt: 2 }),
+        vec![(0, vec![4, 8])]
+    );
+    assert_eq!("clock-driven:5".parse::<SimulationMode>(), Ok(SimulationMode::ClockDriven { dt: 5 }));
+
+    // a spike moved by the highest bit of its timestamp is past the last tick of the clock
+    assert_eq!(nn.solve_clock_driven(vec![(0, vec![1, 1 << 127])], 1), vec![(0, vec![2])]);
+    let last = MAX_CLOCK_TICKS;
+    assert_eq!(nn.solve_clock_driven(vec![(0, vec![1, last])], 1), vec![(0, vec![2, last + 1])]);
+    assert_eq!(nn.solve_clock_driven(vec![(0, vec![1, last + 1])], 1), vec![(0, vec![2])]);
+    assert!("sequential:5".parse::<SimulationMode>().is_err());
+}
@@ -0,0 +1,99 @@
+use nalgebra::DMatrix;
+use spiking_nn_resilience::lif::{LeakyIntegrateFire, LifNeuron};
+use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson};
+use spiking_nn_resilience::snn::Spike;
+use spiking_nn_resilience::NN;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_stream_same_as_sequential() {
+    let nn = NeuronJson::read_from_file(
+        "./tests/layers.json",
+        "./tests/weights.json",
+        "./tests/configurations.json",
+    )
+    .unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+
+    let last = nn.layers.len() - 1;
+    let mut output: Vec<(u128, Vec<u128>)> = (0..nn.layers[last].num_neurons())
+        .map(|n| (n as u128, vec![]))
+        .collect();
+    for spike in nn.stream(Spike::vec_of_all_spikes(input.clone())) {
+        assert_eq!(spike.layer_id, last);
+        output[spike.neuron_id].1.push(spike.ts);
+    }
+    assert_eq!(nn.solve_sequential(input), output);
+}
+
+#[test]
+fn test_stream_from_channel() {
+    // v_rest 0, v_reset 0, v_th 0.5, tau 1: the neuron fires at every input spike
+    let nn = NN::<LeakyIntegrateFire>::new()
+        .layer(
+            vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0)],
+            DMatrix::from_vec(1, 1, vec![1.0]),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+        )
+        .unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let (out_tx, out_rx) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        for spike in nn.stream(rx) {
+            out_tx.send(spike.ts).unwrap();
+        }
+    });
+    // the spikes of a time are handled as soon as a later spike arrives
+    tx.send(Spike::new(1, 0, 0)).unwrap();
+    tx.send(Spike::new(4, 0, 0)).unwrap();
+    assert_eq!(out_rx.recv().unwrap(), 2);
+    // an older spike is received with the current ones
+    tx.send(Spike::new(3, 0, 0)).unwrap();
+    tx.send(Spike::new(10, 0, 0)).unwrap();
+    assert_eq!(out_rx.recv().unwrap(), 5);
+    drop(tx);
+    handle.join().unwrap();
+    assert_eq!(out_rx.iter().collect::<Vec<_>>(), vec![11]);
+
+    // the spikes cross the hidden layers without waiting for the next input
+    let nn = NN::<LeakyIntegrateFire>::new()
+        .layer(
+            vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0)],
+            DMatrix::from_vec(1, 1, vec![1.0]),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+        )
+        .unwrap()
+        .layer(
+            vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0)],
+            DMatrix::from_vec(1, 1, vec![1.0]),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+        )
+        .unwrap();
+    let (tx, rx) = mpsc::channel();
+    let (out_tx, out_rx) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        for spike in nn.stream(rx) {
+            out_tx.send(spike.ts).unwrap();
+        }
+    });
+    tx.send(Spike::new(1, 0, 0)).unwrap();
+    tx.send(Spike::new(10, 0, 0)).unwrap();
+    assert_eq!(out_rx.recv_timeout(Duration::from_secs(10)), Ok(3));
+    drop(tx);
+    handle.join().unwrap();
+    assert_eq!(out_rx.iter().collect::<Vec<_>>(), vec![12]);
+
+    // a long input, never held in memory as a whole
+    let nn = NN::<LeakyIntegrateFire>::new()
+        .layer(
+            vec![LifNeuron::new(0.0, 0.0, 0.5, 1.0)],
+            DMatrix::from_vec(1, 1, vec![1.0]),
+            DMatrix::from_vec(1, 1, vec![0.0]),
+        )
+        .unwrap();
+    let input = (1..=1_000_000).map(|ts| Spike::new(2 * ts, 0, 0));
+    assert_eq!(nn.stream(input).count(), 1_000_000);
+}
@@ -1,8 +1,11 @@
 // use std::time::{SystemTime, UNIX_EPOCH};
 
 use nalgebra::DMatrix;
-use spiking_nn_resilience::lif::{Configuration, LeakyIntegrateFire, LifNeuron};
+use spiking_nn_resilience::lif::{
+    ComparatorInjection, ComparatorOperand, Configuration, LeakyIntegrateFire, LifNeuron, ResetFault, ResetInjection,
+};
 use spiking_nn_resilience::snn::json_adapter::{InputJson, NeuronJson, ResilienceJson};
+use spiking_nn_resilience::snn::mitigation::{RangeAction, RangeCheck, RangeCheckConfig};
 use spiking_nn_resilience::*;
 use spiking_nn_resilience::snn::model::Stuck;
 use spiking_nn_resilience::snn::resilience::Resilience;
@@ -611,3 +614,83 @@ fn test_all_combinations_() {
 //     */
 //     return;
 // }
+
+#[test]
+fn test_range_check_learned() {
+    let nn = NeuronJson::read_from_file("./tests/layers.json", "./tests/weights.json", "./tests/configurations.json").unwrap();
+    let input = InputJson::read_input_from_file("./tests/input_spikes.json");
+    let right_output = nn.clone().solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+
+    // the fault free network never triggers the learned ranges
+    let range_check = RangeCheck::learn(&nn, input.clone(), 0.1, RangeAction::Clamp);
+    let mut nn_checked = nn.clone();
+    nn_checked.use_range_check(&range_check);
+    let res = nn_checked.solve_multiple_vec_spike(input.clone()).lock().unwrap().clone();
+    assert_eq!(res, right_output);
+    assert_eq!(range_check.stats().total(), 0);
+
+    // a huge threshold is repaired
+    let range_check = range_check.fresh();
+    let mut nn_faulty = nn.clone();
+    nn_faulty.layers[0].get_neuron_mut(0).unwrap().v_th = f64::MAX;
+    nn_faulty.use_range_check(&range_check);
+    nn_faulty.solve_multiple_vec_spike(input.clone());
+    assert_eq!(range_check.stats().v_th, 1);
+
+    let report = Resilience::new(vec!["neurons".to_string()], Stuck::Transient, 20)
+        .with_range_check(RangeCheckConfig::Learned { margin: 0.1, action: RangeAction::Reset })
+        .run_resilience_test(nn, input);
+    assert!(report.mitigated_right_outputs.is_some());
+    assert_eq!(report.right_output, right_output);
+}
+
+#[test]
+fn test_comparator_bit() {
+    let conf = Configuration::new(0.0, 0.0, 1.0, 1.0);
+
+    // the sign bit of the threshold stuck at one: the neuron fires with any positive v_mem
+    let mut neuron = LifNeuron::from_conf(&conf);
+    neuron.comparator = Some(ComparatorInjection::new(Stuck::One, ComparatorOperand::VTh, 63));
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, 0.5, 1), 1.0);
+    assert_eq!(neuron.v_th, 1.0);
+
+    // the exponent bits of v_mem stuck at zero: the neuron never fires
+    let mut neuron = LifNeuron::from_conf(&conf);
+    neuron.comparator = Some(ComparatorInjection::new(Stuck::Zero, ComparatorOperand::VMem, 62));
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, 5.0, 1), 0.0);
+    assert_eq!(neuron.v_mem, 5.0);
+
+    // the exponent of v_mem all ones: a positive NaN is above any threshold, a negative one below
+    let mut neuron = LifNeuron::from_conf(&Configuration::new(0.0, 0.0, 2.0, 1.0));
+    neuron.comparator = Some(ComparatorInjection::new(Stuck::One, ComparatorOperand::VMem, 62));
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, 1.5, 1), 1.0);
+    let mut neuron = LifNeuron::from_conf(&Configuration::new(0.0, 0.0, -2.0, 1.0));
+    neuron.comparator = Some(ComparatorInjection::new(Stuck::One, ComparatorOperand::VMem, 62));
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, -1.5, 1), 0.0);
+}
+
+#[test]
+fn test_reset_faults() {
+    let conf = Configuration::new(0.0, 0.0, 1.0, 1.0);
+    let faulty = |fault: ResetFault| {
+        let mut neuron = LifNeuron::from_conf(&conf);
+        neuron.reset = Some(ResetInjection::new(fault, Stuck::One, 62));
+        neuron
+    };
+
+    let mut neuron = faulty(ResetFault::NotApplied);
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, 2.0, 1), 1.0);
+    assert_eq!(neuron.v_mem, 2.0);
+
+    // the input of the update after the spike is lost
+    let mut neuron = faulty(ResetFault::AppliedTwice);
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, 2.0, 1), 1.0);
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, 2.0, 1), 0.0);
+    assert_eq!(neuron.v_mem, 0.0);
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, 2.0, 1), 1.0);
+
+    let mut neuron = faulty(ResetFault::Corrupted);
+    assert_eq!(LeakyIntegrateFire::handle_spike(&mut neuron, 2.0, 1), 1.0);
+    assert_eq!(neuron.v_mem, f64::from_bits(1u64 << 62));
+    assert_eq!(neuron.v_reset, 0.0);
+}